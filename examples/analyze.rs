@@ -25,7 +25,11 @@ fn main() {
             println!("  Short Name: '{}'", fcm.file_header.short_name);
             println!("  Long Name: '{}'", fcm.file_header.long_name);
             println!("  Author: '{}'", fcm.file_header.author_name);
-            println!("  Generator: {:?}", fcm.file_header.generator);
+            println!(
+                "  Generator: {:?} ({})",
+                fcm.file_header.generator,
+                fcm.file_header.generator.description()
+            );
             println!("  Print-to-Cut Flag: {:?}", fcm.file_header.print_to_cut);
             println!("  Thumbnail Size: {}x{}",
                 fcm.file_header.thumbnail_block_size_width,
@@ -54,7 +58,7 @@ fn main() {
             println!("\n--- Piece Table ---");
             println!("  Number of pieces: {}", fcm.piece_table.pieces.len());
 
-            for (i, (id, piece)) in fcm.piece_table.pieces.iter().enumerate() {
+            for (i, (id, piece)) in fcm.piece_table.iter().enumerate() {
                 println!("\n  Piece {} (id={}):", i, id);
                 println!("    Dimensions: {}x{} ({}mm x {}mm)",
                     piece.width, piece.height,
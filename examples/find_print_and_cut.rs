@@ -1,8 +1,6 @@
 // Scan all sample FCM files to find print-and-cut examples
 // Run with: cargo run --example find_print_and_cut
 
-use fcmlib::FcmFile;
-use std::fs;
 use std::path::Path;
 
 fn main() {
@@ -13,35 +11,15 @@ fn main() {
         return;
     }
 
-    let mut print_and_cut_files = Vec::new();
-    let mut total_files = 0;
+    // Only the header and cut data are read, the geometry is skipped
+    let summaries = fcmlib::scan_directory(samples_dir).unwrap();
+    let print_and_cut_files: Vec<_> = summaries.iter().filter(|s| s.is_print_and_cut).collect();
 
-    for entry in fs::read_dir(samples_dir).unwrap() {
-        let entry = entry.unwrap();
-        let path = entry.path();
-
-        if path.extension().map_or(false, |e| e == "fcm") {
-            total_files += 1;
-
-            if let Ok(fcm) = FcmFile::from_file(&path) {
-                if let Some(alignment) = &fcm.cut_data.alignment {
-                    print_and_cut_files.push((
-                        path.file_name().unwrap().to_string_lossy().to_string(),
-                        fcm.cut_data.file_type,
-                        alignment.needed,
-                        alignment.marks.len(),
-                        fcm.file_header.variant,
-                    ));
-                }
-            }
-        }
-    }
-
-    println!("Scanned {} FCM files", total_files);
+    println!("Scanned {} FCM files", summaries.len());
     println!("\nPrint-and-Cut files found: {}\n", print_and_cut_files.len());
 
-    for (name, file_type, needed, mark_count, variant) in &print_and_cut_files {
-        println!("  {} - {:?}, variant={:?}, needed={}, marks={}",
-            name, file_type, variant, needed, mark_count);
+    for summary in &print_and_cut_files {
+        println!("  {} - {:?}, variant={:?}, pieces={}, marks={}",
+            summary.name, summary.file_type, summary.variant, summary.piece_count, summary.marks);
     }
 }
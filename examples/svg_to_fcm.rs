@@ -5,7 +5,7 @@
 use fcmlib::{
     svg_path::{SvgConfig, SvgPathParser},
     AlignmentData, CutData, FcmFile, FileHeader, FileType, FileVariant,
-    Generator, Path, PathTool, Piece, PieceRestrictions, PieceTable, Point,
+    Generator, Path, PathTool, PieceBuilder, PieceTable, Point,
 };
 
 /// Standard page sizes in mm
@@ -16,6 +16,7 @@ struct PageSize {
 
 impl PageSize {
     const LETTER: PageSize = PageSize { width_mm: 215.9, height_mm: 279.4 };
+    #[allow(dead_code)]
     const A4: PageSize = PageSize { width_mm: 210.0, height_mm: 297.0 };
 }
 
@@ -48,109 +49,26 @@ fn svg_to_print_and_cut_fcm(
         scale: 1.0,
         offset_x_mm: 0.0,
         offset_y_mm: 0.0,
+        flip_y_mm: None,
+        ..Default::default()
     };
 
     let parser = SvgPathParser::new(config);
     let shapes = parser.parse(svg_path_d)?;
 
-    // Calculate bounding box for piece dimensions
-    let mut min_x = i32::MAX;
-    let mut min_y = i32::MAX;
-    let mut max_x = i32::MIN;
-    let mut max_y = i32::MIN;
-
-    for shape in &shapes {
-        min_x = min_x.min(shape.start.x);
-        min_y = min_y.min(shape.start.y);
-        max_x = max_x.max(shape.start.x);
-        max_y = max_y.max(shape.start.y);
-
-        for outline in &shape.outlines {
-            match outline {
-                fcmlib::Outline::Line(segments) => {
-                    for seg in segments {
-                        min_x = min_x.min(seg.end.x);
-                        min_y = min_y.min(seg.end.y);
-                        max_x = max_x.max(seg.end.x);
-                        max_y = max_y.max(seg.end.y);
-                    }
-                }
-                fcmlib::Outline::Bezier(segments) => {
-                    for seg in segments {
-                        min_x = min_x.min(seg.end.x).min(seg.control1.x).min(seg.control2.x);
-                        min_y = min_y.min(seg.end.y).min(seg.control1.y).min(seg.control2.y);
-                        max_x = max_x.max(seg.end.x).max(seg.control1.x).max(seg.control2.x);
-                        max_y = max_y.max(seg.end.y).max(seg.control1.y).max(seg.control2.y);
-                    }
-                }
-            }
-        }
-    }
-
-    let width = (max_x - min_x) as u32;
-    let height = (max_y - min_y) as u32;
-    let center_x = (min_x + max_x) as f32 / 2.0;
-    let center_y = (min_y + max_y) as f32 / 2.0;
-
     // Create paths from shapes
     let paths: Vec<Path> = shapes
         .into_iter()
         .map(|shape| Path {
             tool: PathTool::TOOL_CUT | PathTool::TOOL_DRAW,
-            shape: Some(fcmlib::PathShape {
-                start: Point {
-                    x: shape.start.x - center_x as i32,
-                    y: shape.start.y - center_y as i32,
-                },
-                outlines: shape.outlines.into_iter().map(|outline| {
-                    match outline {
-                        fcmlib::Outline::Line(segments) => {
-                            fcmlib::Outline::Line(segments.into_iter().map(|seg| {
-                                fcmlib::SegmentLine {
-                                    end: Point {
-                                        x: seg.end.x - center_x as i32,
-                                        y: seg.end.y - center_y as i32,
-                                    }
-                                }
-                            }).collect())
-                        }
-                        fcmlib::Outline::Bezier(segments) => {
-                            fcmlib::Outline::Bezier(segments.into_iter().map(|seg| {
-                                fcmlib::SegmentBezier {
-                                    control1: Point {
-                                        x: seg.control1.x - center_x as i32,
-                                        y: seg.control1.y - center_y as i32,
-                                    },
-                                    control2: Point {
-                                        x: seg.control2.x - center_x as i32,
-                                        y: seg.control2.y - center_y as i32,
-                                    },
-                                    end: Point {
-                                        x: seg.end.x - center_x as i32,
-                                        y: seg.end.y - center_y as i32,
-                                    },
-                                }
-                            }).collect())
-                        }
-                    }
-                }).collect(),
-            }),
+            shape: Some(shape),
             rhinestone_diameter: None,
             rhinestones: vec![],
         })
         .collect();
 
-    // Create piece
-    let piece = Piece {
-        width,
-        height,
-        transform: Some((1.0, 0.0, 0.0, 1.0, center_x, center_y)),
-        expansion_limit_value: 0,
-        reduction_limit_value: 0,
-        restriction_flags: PieceRestrictions::empty(),
-        label: String::new(),
-        paths,
-    };
+    // Create piece, centered on its bounds
+    let piece = PieceBuilder::new().paths(paths).build();
 
     // Page dimensions in FCM units
     let page_width = (page.width_mm * 100.0) as u32;
@@ -163,8 +81,8 @@ fn svg_to_print_and_cut_fcm(
             version: String::from("0100"),
             content_id: 400000002,
             short_name: String::new(),
-            long_name: String::from(" "),
-            author_name: String::from(" "),
+            long_name: String::from(FileHeader::EMPTY_NAME),
+            author_name: String::from(FileHeader::EMPTY_NAME),
             copyright: String::new(),
             thumbnail_block_size_width: 3,
             thumbnail_block_size_height: 3,
@@ -186,6 +104,7 @@ fn svg_to_print_and_cut_fcm(
         piece_table: PieceTable {
             pieces: vec![(0, piece)],
         },
+        trailer: vec![],
     })
 }
 
@@ -1,6 +1,7 @@
 use crate::encode::Encode;
 use crate::point;
 use crate::point::Point;
+use crate::registration_marks::{dimensions, PageSize};
 use crate::util::bool32;
 use nom::combinator::map;
 use nom::multi::length_count;
@@ -11,9 +12,63 @@ use nom::IResult;
 #[derive(Debug)]
 pub struct AlignmentData {
     pub needed: bool,
+    /// Registration mark positions in page coordinates. The file stores the
+    /// count before the marks, so layouts other than the four corners that
+    /// Canvas Workspace writes are read and written as they are.
     pub marks: Vec<Point>,
 }
 
+impl AlignmentData {
+    /// The section as it is serialized in the file: the `needed` flag, the mark
+    /// count and the marks, all little-endian. Useful for comparing files that
+    /// don't scan against ones that do.
+    pub fn raw_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        // Writing to a Vec can't fail
+        let _ = self.encode(&mut buffer);
+        buffer
+    }
+
+    /// Check that every mark can be printed and scanned on `page`: its outer
+    /// ring has to lie on the page and must not touch another mark's ring.
+    /// Returns the indices of the marks that fail, in order.
+    pub fn validate_against(&self, page: &PageSize) -> Result<(), Vec<usize>> {
+        let radius = (dimensions::OUTER_RING_OUTER_MM * 100.0).round() as i64;
+        let (width, height) = page.to_fcm_units();
+        let on_page = |mark: &Point| {
+            let (x, y) = (mark.x as i64, mark.y as i64);
+            x - radius >= 0
+                && y - radius >= 0
+                && x + radius <= width as i64
+                && y + radius <= height as i64
+        };
+        let overlaps = |a: &Point, b: &Point| {
+            let (dx, dy) = ((a.x - b.x) as i64, (a.y - b.y) as i64);
+            dx * dx + dy * dy < 4 * radius * radius
+        };
+
+        let invalid: Vec<usize> = self
+            .marks
+            .iter()
+            .enumerate()
+            .filter(|(i, mark)| {
+                !on_page(mark)
+                    || self
+                        .marks
+                        .iter()
+                        .enumerate()
+                        .any(|(j, other)| j != *i && overlaps(mark, other))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+}
+
 pub(crate) fn read_alignment_data(input: &[u8]) -> IResult<&[u8], AlignmentData> {
     map(
         tuple((bool32, length_count(le_u32, point::read_point))),
@@ -31,3 +86,52 @@ impl Encode for AlignmentData {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registration_marks;
+
+    #[test]
+    fn test_validate_against() {
+        let page = PageSize::A4;
+        let mut alignment = AlignmentData {
+            needed: true,
+            marks: registration_marks::get_fcm_alignment_marks(&page),
+        };
+        assert_eq!(alignment.validate_against(&page), Ok(()));
+
+        // Beyond the right edge, and a ring reaching past the top
+        alignment.marks[1].x = 21500;
+        alignment.marks[2].y = 300;
+        assert_eq!(alignment.validate_against(&page), Err(vec![1, 2]));
+
+        // Two marks on top of each other
+        let mut alignment = AlignmentData {
+            needed: true,
+            marks: registration_marks::get_fcm_alignment_marks(&page),
+        };
+        alignment.marks[3] = Point {
+            x: alignment.marks[0].x + 500,
+            y: alignment.marks[0].y,
+        };
+        assert_eq!(alignment.validate_against(&page), Err(vec![0, 3]));
+    }
+
+    #[test]
+    fn test_raw_bytes() {
+        let alignment = AlignmentData {
+            needed: true,
+            marks: vec![Point { x: 1000, y: 1500 }, Point { x: -20, y: 30000 }],
+        };
+        let bytes = alignment.raw_bytes();
+        assert_eq!(bytes.len(), 8 + 2 * 8);
+        assert_eq!(&bytes[..8], &[1, 0, 0, 0, 2, 0, 0, 0]);
+
+        let (rest, parsed) = read_alignment_data(&bytes).unwrap();
+        assert!(rest.is_empty());
+        assert!(parsed.needed);
+        let marks: Vec<_> = parsed.marks.iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(marks, vec![(1000, 1500), (-20, 30000)]);
+    }
+}
@@ -0,0 +1,393 @@
+//! Boolean operations on closed shapes
+//!
+//! Shapes are flattened to polygons and combined using the nonzero fill rule,
+//! so a hole has to wind opposite to the outline it sits in, as it does in the
+//! results of these functions.
+//!
+//! Results are returned as one closed Line shape per contour. Outer contours
+//! wind counter-clockwise (positive [`PathShape::signed_area`]), holes wind
+//! clockwise.
+//!
+//! # Example
+//! ```
+//! use fcmlib::boolean;
+//! use fcmlib::svg_path::{SvgConfig, SvgPathParser};
+//!
+//! let parser = SvgPathParser::new(SvgConfig::default());
+//! let mut shapes = parser.parse("M 0,0 L 100,0 L 100,100 L 0,100 Z").unwrap();
+//! shapes.extend(parser.parse("M 50,50 L 150,50 L 150,150 L 50,150 Z").unwrap());
+//!
+//! let welded = boolean::union(&shapes);
+//! assert_eq!(welded.len(), 1);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::geometry::{self, from_f64, to_f64};
+use crate::{PathShape, Point};
+
+type Vec2 = (f64, f64);
+
+/// Distance at which the sides of an edge are sampled
+const SIDE_OFFSET: f64 = 0.01;
+/// Parameter tolerance when intersecting edges
+const EPSILON: f64 = 1e-9;
+
+/// Weld shapes into the outlines of the area covered by any of them
+pub fn union(shapes: &[PathShape]) -> Vec<PathShape> {
+    combine(shapes, &[], |a, _| a)
+}
+
+/// Cut the area covered by `b` out of the area covered by `a`
+pub fn difference(a: &[PathShape], b: &[PathShape]) -> Vec<PathShape> {
+    combine(a, b, |a, b| a && !b)
+}
+
+/// Keep only the area covered by both `a` and `b`
+pub fn intersection(a: &[PathShape], b: &[PathShape]) -> Vec<PathShape> {
+    combine(a, b, |a, b| a && b)
+}
+
+/// Combine two sets of shapes, keeping the area where `keep` returns true
+/// for (inside `a`, inside `b`)
+fn combine<F: Fn(bool, bool) -> bool>(a: &[PathShape], b: &[PathShape], keep: F) -> Vec<PathShape> {
+    let a = to_polygons(a);
+    let b = to_polygons(b);
+
+    let mut edges: Vec<(Vec2, Vec2)> = Vec::new();
+    for polygon in a.iter().chain(b.iter()) {
+        for i in 0..polygon.len() {
+            edges.push((polygon[i], polygon[(i + 1) % polygon.len()]));
+        }
+    }
+
+    let mut kept: Vec<(Vec2, Vec2)> = Vec::new();
+    for (from, to) in split_edges(&edges) {
+        let mid = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+        let length = geometry::length(to.0 - from.0, to.1 - from.1);
+        let normal = (-(to.1 - from.1) / length, (to.0 - from.0) / length);
+        let left = (
+            mid.0 + normal.0 * SIDE_OFFSET,
+            mid.1 + normal.1 * SIDE_OFFSET,
+        );
+        let right = (
+            mid.0 - normal.0 * SIDE_OFFSET,
+            mid.1 - normal.1 * SIDE_OFFSET,
+        );
+
+        let inside_left = keep(inside(&a, left), inside(&b, left));
+        let inside_right = keep(inside(&a, right), inside(&b, right));
+        let edge = match (inside_left, inside_right) {
+            (true, false) => (from, to),
+            (false, true) => (to, from),
+            _ => continue,
+        };
+        if !kept.contains(&edge) {
+            kept.push(edge);
+        }
+    }
+
+    link_rings(kept)
+        .into_iter()
+        .filter_map(|ring| {
+            let points = simplify(ring.iter().map(|&(x, y)| from_f64(x, y)).collect());
+            if points.len() < 3 {
+                None
+            } else {
+                Some(geometry::polygon_to_shape(&points))
+            }
+        })
+        .collect()
+}
+
+fn to_polygons(shapes: &[PathShape]) -> Vec<Vec<Vec2>> {
+    shapes
+        .iter()
+        .filter_map(|shape| {
+            let mut points: Vec<Vec2> = shape
+                .to_polyline(geometry::DEFAULT_TOLERANCE)
+                .into_iter()
+                .map(to_f64)
+                .collect();
+            points.dedup();
+            if points.len() > 1 && points[0] == points[points.len() - 1] {
+                points.pop();
+            }
+            if points.len() < 3 {
+                None
+            } else {
+                Some(points)
+            }
+        })
+        .collect()
+}
+
+/// Split every edge at every point where it meets another edge
+fn split_edges(edges: &[(Vec2, Vec2)]) -> Vec<(Vec2, Vec2)> {
+    let mut splits: Vec<Vec<(f64, Vec2)>> = vec![Vec::new(); edges.len()];
+
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            let (a0, a1) = edges[i];
+            let (b0, b1) = edges[j];
+            let r = sub(a1, a0);
+            let s = sub(b1, b0);
+            let denominator = cross(r, s);
+
+            if denominator.abs() > EPSILON * norm(r) * norm(s) {
+                let qp = sub(b0, a0);
+                let t = cross(qp, s) / denominator;
+                let u = cross(qp, r) / denominator;
+                if !(-EPSILON..=1.0 + EPSILON).contains(&t)
+                    || !(-EPSILON..=1.0 + EPSILON).contains(&u)
+                {
+                    continue;
+                }
+                // Reuse exact end points so split edges link up again
+                let point = if t.abs() <= EPSILON {
+                    a0
+                } else if (1.0 - t).abs() <= EPSILON {
+                    a1
+                } else if u.abs() <= EPSILON {
+                    b0
+                } else if (1.0 - u).abs() <= EPSILON {
+                    b1
+                } else {
+                    (a0.0 + r.0 * t, a0.1 + r.1 * t)
+                };
+                splits[i].push((t, point));
+                splits[j].push((u, point));
+            } else if cross(sub(b0, a0), r).abs() <= EPSILON * norm(r) * norm(sub(b0, a0)).max(1.0)
+            {
+                // Collinear, split each edge at the other's end points
+                for point in [b0, b1] {
+                    splits[i].push((project(a0, r, point), point));
+                }
+                for point in [a0, a1] {
+                    splits[j].push((project(b0, s, point), point));
+                }
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    for (i, &(from, to)) in edges.iter().enumerate() {
+        let mut points: Vec<(f64, Vec2)> = splits[i]
+            .iter()
+            .copied()
+            .filter(|(t, _)| *t > EPSILON && *t < 1.0 - EPSILON)
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut current = from;
+        for (_, point) in points.into_iter().chain([(1.0, to)]) {
+            if point != current {
+                result.push((current, point));
+                current = point;
+            }
+        }
+    }
+    result
+}
+
+/// Join directed edges into closed rings by matching end points
+fn link_rings(edges: Vec<(Vec2, Vec2)>) -> Vec<Vec<Vec2>> {
+    // Adding 0.0 folds -0.0 into 0.0 so equal points share a key
+    let key = |p: Vec2| ((p.0 + 0.0).to_bits(), (p.1 + 0.0).to_bits());
+    let mut outgoing: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+    for (i, (from, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(*from)).or_default().push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let (start, mut current) = edges[first];
+        let mut ring = vec![start];
+
+        while current != start {
+            let next = outgoing
+                .get(&key(current))
+                .and_then(|candidates| candidates.iter().copied().find(|&i| !used[i]));
+            match next {
+                Some(i) => {
+                    used[i] = true;
+                    ring.push(current);
+                    current = edges[i].1;
+                }
+                None => break,
+            }
+        }
+
+        if current == start {
+            rings.push(ring);
+        }
+    }
+    rings
+}
+
+/// Remove repeated and collinear points from a closed ring
+fn simplify(mut points: Vec<Point>) -> Vec<Point> {
+    points.dedup_by(|a, b| (a.x, a.y) == (b.x, b.y));
+    while points.len() > 1
+        && (points[0].x, points[0].y) == (points[points.len() - 1].x, points[points.len() - 1].y)
+    {
+        points.pop();
+    }
+
+    let mut changed = true;
+    while changed && points.len() >= 3 {
+        changed = false;
+        for i in 0..points.len() {
+            let prev = points[(i + points.len() - 1) % points.len()];
+            let next = points[(i + 1) % points.len()];
+            let current = points[i];
+            let d1 = (
+                current.x as i64 - prev.x as i64,
+                current.y as i64 - prev.y as i64,
+            );
+            let d2 = (
+                next.x as i64 - current.x as i64,
+                next.y as i64 - current.y as i64,
+            );
+            if d1.0 * d2.1 - d1.1 * d2.0 == 0 {
+                points.remove(i);
+                changed = true;
+                break;
+            }
+        }
+    }
+    points
+}
+
+/// Nonzero winding test of `point` against a set of polygons
+fn inside(polygons: &[Vec<Vec2>], point: Vec2) -> bool {
+    let mut winding = 0;
+    for polygon in polygons {
+        for i in 0..polygon.len() {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % polygon.len()];
+            let side = cross(sub(b, a), sub(point, a));
+            if a.1 <= point.1 {
+                if b.1 > point.1 && side > 0.0 {
+                    winding += 1;
+                }
+            } else if b.1 <= point.1 && side < 0.0 {
+                winding -= 1;
+            }
+        }
+    }
+    winding != 0
+}
+
+fn project(origin: Vec2, direction: Vec2, point: Vec2) -> f64 {
+    let d = sub(point, origin);
+    (d.0 * direction.0 + d.1 * direction.1)
+        / (direction.0 * direction.0 + direction.1 * direction.1)
+}
+
+fn sub(a: Vec2, b: Vec2) -> Vec2 {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn cross(a: Vec2, b: Vec2) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn norm(v: Vec2) -> f64 {
+    geometry::length(v.0, v.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg_path::tests::unit_parser;
+
+    fn parse(d: &str) -> Vec<PathShape> {
+        unit_parser().parse(d).unwrap()
+    }
+
+    fn circle(cx: i32, cy: i32, r: i32) -> Vec<PathShape> {
+        parse(&format!(
+            "M {},{} A {r},{r} 0 1,1 {},{} A {r},{r} 0 1,1 {},{} Z",
+            cx - r,
+            cy,
+            cx + r,
+            cy,
+            cx - r,
+            cy
+        ))
+    }
+
+    #[test]
+    fn test_union_overlapping_circles() {
+        let mut shapes = circle(0, 0, 1000);
+        shapes.extend(circle(1500, 0, 1000));
+
+        let result = union(&shapes);
+        assert_eq!(result.len(), 1);
+
+        let (min, max) = result[0].bounds();
+        assert!((min.x - -1000).abs() <= 2 && (max.x - 2500).abs() <= 2);
+        assert!(result[0].signed_area().abs() > shapes[0].signed_area().abs());
+    }
+
+    #[test]
+    fn test_union_disjoint() {
+        let mut shapes = parse("M 0,0 L 100,0 L 100,100 L 0,100 Z");
+        shapes.extend(parse("M 200,0 L 300,0 L 300,100 L 200,100 Z"));
+
+        assert_eq!(union(&shapes).len(), 2);
+    }
+
+    #[test]
+    fn test_union_creates_hole() {
+        // Four bars forming a frame
+        let shapes = parse(
+            "M 0,0 L 300,0 L 300,100 L 0,100 Z \
+             M 0,200 L 300,200 L 300,300 L 0,300 Z \
+             M 0,0 L 100,0 L 100,300 L 0,300 Z \
+             M 200,0 L 300,0 L 300,300 L 200,300 Z",
+        );
+
+        let mut result = union(&shapes);
+        assert_eq!(result.len(), 2);
+        result.sort_by(|a, b| b.signed_area().total_cmp(&a.signed_area()));
+        assert_eq!(result[0].signed_area(), 90000.0);
+        assert_eq!(result[1].signed_area(), -10000.0);
+    }
+
+    #[test]
+    fn test_difference_makes_hole() {
+        let outer = parse("M 0,0 L 300,0 L 300,300 L 0,300 Z");
+        let inner = parse("M 100,100 L 200,100 L 200,200 L 100,200 Z");
+
+        let mut result = difference(&outer, &inner);
+        assert_eq!(result.len(), 2);
+        result.sort_by(|a, b| b.signed_area().total_cmp(&a.signed_area()));
+        assert_eq!(result[0].signed_area(), 90000.0);
+        assert_eq!(result[1].signed_area(), -10000.0);
+
+        // Subtracting the hole again changes nothing
+        assert_eq!(difference(&result, &inner).len(), 2);
+    }
+
+    #[test]
+    fn test_intersection_clips() {
+        let frame = parse("M 0,0 L 200,0 L 200,200 L 0,200 Z");
+        let shape = parse("M 100,100 L 300,100 L 300,300 L 100,300 Z");
+
+        let result = intersection(&frame, &shape);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].signed_area(), 10000.0);
+        let (min, max) = result[0].bounds();
+        assert_eq!((min.x, min.y, max.x, max.y), (100, 100, 200, 200));
+
+        let apart = parse("M 500,500 L 600,500 L 600,600 L 500,600 Z");
+        assert!(intersection(&frame, &apart).is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+//! Test pattern for calibrating a machine
+//!
+//! The pattern has two pieces, 10mm from the top left corner of the mat:
+//!
+//! - Concentric squares of 10, 20, 30, 40 and 50mm. Comparing the cut sizes
+//!   shows the blade offset and scaling.
+//! - Below them, a 100mm × 10mm ruler with drawn ticks every 10mm, longer
+//!   every 50mm, to check scaling over a longer distance and that pen and
+//!   blade line up.
+//!
+//! Altogether the pattern covers 100mm × 70mm.
+//!
+//! # Example
+//! ```
+//! use fcmlib::calibration;
+//! use fcmlib::registration_marks::PageSize;
+//!
+//! let file = calibration::test_pattern(&PageSize::SQUARE_12);
+//! let bytes = file.to_bytes().unwrap();
+//! ```
+
+use crate::registration_marks::PageSize;
+use crate::{FcmFile, Path, PathShape, PathTool, Piece, Point};
+
+/// Distance of the pattern from the top and left of the mat
+const MARGIN: i32 = 1000;
+/// Side of the largest square
+const SQUARES_SIZE: i32 = 5000;
+/// Distance between the squares and the ruler
+const RULER_GAP: i32 = 1000;
+const RULER_LENGTH: i32 = 10000;
+const RULER_HEIGHT: i32 = 1000;
+const TICK_SPACING: i32 = 1000;
+
+/// Cut file with the calibration pattern on `page`
+pub fn test_pattern(page: &PageSize) -> FcmFile {
+    let center = MARGIN + SQUARES_SIZE / 2;
+    let squares = (1..=5)
+        .map(|i| {
+            let half = i * SQUARES_SIZE / 10;
+            rectangle(center - half, center - half, center + half, center + half)
+        })
+        .collect();
+    let mut file = FcmFile::from_shapes(squares, PathTool::TOOL_CUT, page);
+
+    let top = MARGIN + SQUARES_SIZE + RULER_GAP;
+    let mut paths = vec![Path {
+        tool: PathTool::TOOL_CUT,
+        shape: Some(rectangle(
+            MARGIN,
+            top,
+            MARGIN + RULER_LENGTH,
+            top + RULER_HEIGHT,
+        )),
+        rhinestone_diameter: None,
+        rhinestones: vec![],
+    }];
+    for i in 0..=RULER_LENGTH / TICK_SPACING {
+        let x = MARGIN + i * TICK_SPACING;
+        let length = if i % 5 == 0 {
+            RULER_HEIGHT / 2
+        } else {
+            RULER_HEIGHT / 4
+        };
+        let points = [Point { x, y: top }, Point { x, y: top + length }];
+        paths.push(Path {
+            tool: PathTool::TOOL_DRAW | PathTool::PATH_OPEN,
+            shape: Some(PathShape::from_fcm_points(&points, false)),
+            rhinestone_diameter: None,
+            rhinestones: vec![],
+        });
+    }
+    file.piece_table.pieces.push((1, Piece::from_paths(paths)));
+    file
+}
+
+fn rectangle(min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> PathShape {
+    let corners = [
+        (min_x, min_y),
+        (max_x, min_y),
+        (max_x, max_y),
+        (min_x, max_y),
+    ];
+    PathShape::from_fcm_points(&corners.map(|(x, y)| Point { x, y }), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_dimensions() {
+        let file = test_pattern(&PageSize::A4);
+        file.validate().unwrap();
+        assert!(file.fit_warnings(&PageSize::A4, 500).is_empty());
+
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (1000, 1000, 11000, 8000));
+        assert_eq!(file.closed_region_count(), 6);
+
+        let pieces: Vec<_> = file.pieces().map(|(_, piece)| piece).collect();
+        assert_eq!((pieces[0].width, pieces[0].height), (5000, 5000));
+        assert_eq!((pieces[1].width, pieces[1].height), (10000, 1000));
+
+        let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        assert_eq!(parsed.pieces().count(), 2);
+    }
+}
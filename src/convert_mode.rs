@@ -0,0 +1,28 @@
+use crate::path_tool::PathTool;
+
+/// The workflows a file can be created for with
+/// [`FcmFile::from_shapes_with_mode`], each setting the file variant and type,
+/// the path tools and whether registration marks are added
+///
+/// [`FcmFile::from_shapes_with_mode`]: crate::FcmFile::from_shapes_with_mode
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ConvertMode {
+    /// Cut the shapes from plain material, written as an `#FCM` cut file
+    CutOnly,
+    /// Cut the shapes from printed material after scanning the registration
+    /// marks, written as a `#VCM` print-and-cut file
+    PrintAndCut,
+    /// Draw each shape with the pen, then cut along it, written as an `#FCM`
+    /// cut file
+    DrawAndCut,
+}
+
+impl ConvertMode {
+    /// Tool flags of the paths created in this mode
+    pub fn tool(&self) -> PathTool {
+        match self {
+            ConvertMode::CutOnly | ConvertMode::PrintAndCut => PathTool::TOOL_CUT,
+            ConvertMode::DrawAndCut => PathTool::TOOL_CUT | PathTool::TOOL_DRAW,
+        }
+    }
+}
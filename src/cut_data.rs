@@ -5,6 +5,7 @@ use nom::IResult;
 
 use crate::alignment_data::{read_alignment_data, AlignmentData};
 use crate::encode::Encode;
+use crate::error::Error;
 use crate::file_type;
 use crate::file_type::FileType;
 
@@ -18,6 +19,52 @@ pub struct CutData {
     pub alignment: Option<AlignmentData>,
 }
 
+/// Largest cut area size the reader accepts, 10m in FCM units. Mats are at
+/// most 24 inches long, but rolls allow longer cuts.
+const MAX_CUT_SIZE: u32 = 1_000_000;
+
+impl CutData {
+    /// Check that the cut area size is plausible. A size that is only
+    /// plausible with its bytes swapped points at a file written big-endian,
+    /// which would otherwise parse into garbage.
+    pub(crate) fn check_cut_size(&self) -> Result<(), Error> {
+        for (name, value) in [("width", self.cut_width), ("height", self.cut_height)] {
+            if value <= MAX_CUT_SIZE {
+                continue;
+            }
+            let message = if value.swap_bytes() <= MAX_CUT_SIZE {
+                format!(
+                    "Invalid file: cut {0} {1} is implausible, but {2} when read \
+                     big-endian; the file was likely written with the wrong byte order",
+                    name,
+                    value,
+                    value.swap_bytes()
+                )
+            } else {
+                format!("Invalid file: cut {0} {1} is implausible", name, value)
+            };
+            return Err(Error { message });
+        }
+        Ok(())
+    }
+
+    /// Whether the file carries registration mark positions for print and
+    /// cut, even if scanning them is disabled
+    pub fn is_print_and_cut(&self) -> bool {
+        self.alignment
+            .as_ref()
+            .is_some_and(|alignment| !alignment.marks.is_empty())
+    }
+
+    /// Whether the machine has to scan the registration marks before cutting
+    pub fn needs_scanning(&self) -> bool {
+        self.alignment
+            .as_ref()
+            .is_some_and(|alignment| alignment.needed)
+    }
+}
+
+/// Multi-byte fields are little-endian, like everywhere in the format
 pub(crate) fn read_cut_data(input: &[u8]) -> IResult<&[u8], CutData> {
     flat_map(file_type::read_file_type, |file_type| {
         map(
@@ -54,3 +101,75 @@ impl Encode for CutData {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point;
+
+    #[test]
+    fn test_marks_without_scanning() {
+        let cut_data = CutData {
+            file_type: FileType::PrintAndCut,
+            mat_id: 0,
+            cut_width: 29667,
+            cut_height: 29880,
+            seam_allowance_width: 2000,
+            alignment: Some(AlignmentData {
+                needed: false,
+                marks: vec![
+                    Point { x: 1000, y: 1000 },
+                    Point { x: 28667, y: 1000 },
+                    Point { x: 1000, y: 28880 },
+                    Point { x: 28667, y: 28880 },
+                ],
+            }),
+        };
+        let bytes = cut_data.encode_to_vec().unwrap();
+
+        let (rest, parsed) = read_cut_data(&bytes).unwrap();
+        assert!(rest.is_empty());
+        assert!(parsed.is_print_and_cut());
+        assert!(!parsed.needs_scanning());
+        let alignment = parsed.alignment.unwrap();
+        assert!(!alignment.needed);
+        assert_eq!(alignment.marks.len(), 4);
+        assert_eq!(alignment.marks[3].x, 28667);
+    }
+
+    #[test]
+    fn test_check_cut_size() {
+        let mut cut_data = CutData {
+            file_type: FileType::Cut,
+            mat_id: 0,
+            cut_width: 29667,
+            cut_height: 29880,
+            seam_allowance_width: 0,
+            alignment: None,
+        };
+        cut_data.check_cut_size().unwrap();
+
+        cut_data.cut_height = 29880u32.swap_bytes();
+        let error = cut_data.check_cut_size().unwrap_err();
+        assert!(error.to_string().contains("cut height"));
+        assert!(error.to_string().contains("byte order"));
+
+        cut_data.cut_height = 0x0fff_ffff;
+        let error = cut_data.check_cut_size().unwrap_err();
+        assert!(!error.to_string().contains("byte order"));
+    }
+
+    #[test]
+    fn test_cut_only() {
+        let cut_data = CutData {
+            file_type: FileType::Cut,
+            mat_id: 0,
+            cut_width: 29667,
+            cut_height: 29880,
+            seam_allowance_width: 2000,
+            alignment: None,
+        };
+        assert!(!cut_data.is_print_and_cut());
+        assert!(!cut_data.needs_scanning());
+    }
+}
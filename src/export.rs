@@ -0,0 +1,121 @@
+//! Export of FCM files to other formats for previews
+//!
+//! # Example
+//! ```
+//! use fcmlib::registration_marks::PageSize;
+//! use fcmlib::svg_path::{SvgConfig, SvgPathParser};
+//! use fcmlib::{export, FcmFile, PathTool};
+//!
+//! let parser = SvgPathParser::new(SvgConfig::default());
+//! let shapes = parser.parse("M 100,100 L 200,100 L 200,200 Z").unwrap();
+//! let file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+//!
+//! let svg = export::cut_paths_to_svg(&file, &PageSize::A4);
+//! assert!(svg.contains("<path"));
+//! ```
+
+use std::fmt::Write;
+
+use crate::registration_marks::PageSize;
+use crate::{FcmFile, Outline, PathShape, PathTool};
+
+/// Stroke width of the exported outlines, in FCM units
+const STROKE_WIDTH: i32 = 25;
+
+/// Render the paths that are cut as outlines on a page-sized SVG. Drawn paths
+/// and rhinestones are left out. The SVG uses FCM units, with pieces placed
+/// by their transforms and Bezier outlines kept as curves.
+pub fn cut_paths_to_svg(fcm: &FcmFile, page: &PageSize) -> String {
+    let (width, height) = page.to_fcm_units();
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}mm\" height=\"{}mm\" viewBox=\"0 0 {} {}\">",
+        page.width_mm, page.height_mm, width, height
+    );
+
+    for (id, piece) in fcm.pieces() {
+        let (a, b, c, d, tx, ty) = piece.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+        let _ = writeln!(
+            svg,
+            "  <g id=\"piece{}\" transform=\"matrix({} {} {} {} {} {})\">",
+            id, a, b, c, d, tx, ty
+        );
+        for path in &piece.paths {
+            if !path.tool.contains(PathTool::TOOL_CUT) {
+                continue;
+            }
+            if let Some(shape) = &path.shape {
+                let _ = writeln!(
+                    svg,
+                    "    <path d=\"{}\" fill=\"none\" stroke=\"#000\" stroke-width=\"{}\"/>",
+                    path_data(shape),
+                    STROKE_WIDTH
+                );
+            }
+        }
+        svg.push_str("  </g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// SVG path data for a shape, closed with `Z` if it ends where it starts
+fn path_data(shape: &PathShape) -> String {
+    let mut d = format!("M{},{}", shape.start.x, shape.start.y);
+    for outline in &shape.outlines {
+        match outline {
+            Outline::Line(segments) => {
+                for segment in segments {
+                    let _ = write!(d, " L{},{}", segment.end.x, segment.end.y);
+                }
+            }
+            Outline::Bezier(segments) => {
+                for segment in segments {
+                    let _ = write!(
+                        d,
+                        " C{},{} {},{} {},{}",
+                        segment.control1.x,
+                        segment.control1.y,
+                        segment.control2.x,
+                        segment.control2.y,
+                        segment.end.x,
+                        segment.end.y
+                    );
+                }
+            }
+        }
+    }
+    if shape.is_closed() {
+        d.push_str(" Z");
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg_path::tests::unit_parser;
+
+    #[test]
+    fn test_cut_paths_to_svg() {
+        let parser = unit_parser();
+        let shapes = parser
+            .parse("M 0,0 L 1000,0 L 1000,1000 Z M 2000,0 C 2000,1000 3000,1000 3000,0")
+            .unwrap();
+        let mut file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+        let draw = parser.parse("M 0,2000 L 1000,2000").unwrap().remove(0);
+        let mut draw_file = FcmFile::from_shapes(vec![draw], PathTool::TOOL_DRAW, &PageSize::A4);
+        file.piece_table
+            .pieces
+            .push((1, draw_file.piece_table.pieces.remove(0).1));
+
+        let svg = cut_paths_to_svg(&file, &PageSize::A4);
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert!(svg.contains("viewBox=\"0 0 21000 29700\""));
+        assert!(svg.contains("matrix(1 0 0 1 1500 500)"));
+        assert!(svg.contains("M-1500,-500 L-500,-500 L-500,500 L-1500,-500 Z"));
+        assert!(svg.contains("M500,-500 C500,500 1500,500 1500,-500\""));
+    }
+}
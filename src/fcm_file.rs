@@ -1,31 +1,74 @@
+use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
 
-use nom::combinator::map;
+use nom::combinator::{map, rest};
 use nom::sequence::tuple;
 use nom::IResult;
 
+use crate::alignment_data::AlignmentData;
+use crate::convert_mode::ConvertMode;
 use crate::cut_data::CutData;
 use crate::encode::Encode;
 use crate::error::Error;
+use crate::fcm_metadata::FcmMetadata;
 use crate::file_header::FileHeader;
+use crate::file_source::FileSource;
+use crate::file_type::FileType;
+use crate::file_variant::FileVariant;
+use crate::generator::Generator;
+use crate::json::ToJson;
+use crate::mark_scaling::MarkScaling;
+use crate::outline::Outline;
+use crate::path::Path;
+use crate::path_shape::PathShape;
+use crate::path_tool::PathTool;
+use crate::piece::Piece;
 use crate::piece_table::PieceTable;
-use crate::{cut_data, file_header, piece_table};
+use crate::point::Point;
+use crate::registration_marks::{self, PageSize};
+use crate::stroke::JoinStyle;
+use crate::{cut_data, fcm_metadata, file_header, geometry, piece_table, thumbnail};
 
+/// A parsed FCM file: the header with names and a thumbnail, the cut data with
+/// the mat size and registration marks, and the pieces to cut.
+///
+/// None of the sample files embed the source artwork. The thumbnail is the
+/// only image, and the cut paths are the only vector data, so artwork used for
+/// print-and-cut has to be kept alongside the file. Any data after the pieces
+/// is kept in `trailer`, but no sample has any.
 #[derive(Debug)]
 pub struct FcmFile {
     pub file_header: FileHeader,
     pub cut_data: CutData,
     pub piece_table: PieceTable,
+    /// Bytes following the piece table, kept as-is so files from exporters
+    /// that append a footer round-trip unchanged
+    pub trailer: Vec<u8>,
 }
 
 impl FcmFile {
+    /// Parse a file. All multi-byte fields are read little-endian; a cut area
+    /// size that only makes sense with its bytes swapped is reported as a
+    /// byte order mismatch instead of returning garbage.
     pub fn from_bytes(data: &[u8]) -> Result<FcmFile, Error> {
         let (_, file) = read_fcm_file(data).map_err(|e| Error {
             message: format!("Could not parse file: {0}", e),
         })?;
+        file.cut_data.check_cut_size()?;
         Ok(file)
     }
 
+    /// Parse only the header, cut data and piece count, skipping the piece
+    /// geometry, for quickly indexing many files
+    pub fn read_metadata(data: &[u8]) -> Result<FcmMetadata, Error> {
+        let (_, metadata) = fcm_metadata::read_fcm_metadata(data).map_err(|e| Error {
+            message: format!("Could not parse file metadata: {0}", e),
+        })?;
+        metadata.cut_data.check_cut_size()?;
+        Ok(metadata)
+    }
+
     pub fn from_file<T: AsRef<std::path::Path>>(file: T) -> Result<FcmFile, Error> {
         let data = fs::read(file.as_ref()).map_err(|e| Error {
             message: format!("Could not open file: {0}", e),
@@ -33,12 +76,717 @@ impl FcmFile {
         FcmFile::from_bytes(data.as_slice())
     }
 
+    /// Create a cut file with a single piece containing the given shapes,
+    /// which are in page coordinates
+    pub fn from_shapes(shapes: Vec<PathShape>, tool: PathTool, page: &PageSize) -> FcmFile {
+        let paths = shapes
+            .into_iter()
+            .map(|shape| Path {
+                tool,
+                shape: Some(shape),
+                rhinestone_diameter: None,
+                rhinestones: vec![],
+            })
+            .collect();
+        FcmFile::from_pieces(vec![Piece::from_paths(paths)], page)
+    }
+
+    /// Create a cut file with the given pieces, numbered in order from 0.
+    /// Piece ids are 16 bit, so pieces past the last id are left out.
+    pub fn from_pieces(pieces: Vec<Piece>, page: &PageSize) -> FcmFile {
+        let pieces: Vec<(u16, Piece)> = (0..=u16::MAX).zip(pieces).collect();
+        let page_paths: Vec<Path> = pieces
+            .iter()
+            .flat_map(|(_, piece)| {
+                piece.paths.iter().map(|path| {
+                    let mut path = path.clone();
+                    path.map_points(|p| piece.to_page_point(p));
+                    path
+                })
+            })
+            .collect();
+        let (cut_width, cut_height) = page.to_fcm_units();
+
+        FcmFile {
+            file_header: FileHeader {
+                variant: FileVariant::FCM,
+                version: String::from("0100"),
+                content_id: 400000002,
+                short_name: String::new(),
+                long_name: String::from(FileHeader::EMPTY_NAME),
+                author_name: String::from(FileHeader::EMPTY_NAME),
+                copyright: String::new(),
+                thumbnail_block_size_width: 3,
+                thumbnail_block_size_height: 3,
+                thumbnail: thumbnail::render_paths(
+                    &page_paths,
+                    thumbnail::WIDTH,
+                    thumbnail::HEIGHT,
+                ),
+                generator: Generator::App(1),
+                print_to_cut: None,
+            },
+            cut_data: CutData {
+                file_type: FileType::Cut,
+                mat_id: 0,
+                cut_width,
+                cut_height,
+                seam_allowance_width: 2000,
+                alignment: None,
+            },
+            piece_table: PieceTable { pieces },
+            trailer: vec![],
+        }
+    }
+
+    /// Create a file for `mode` with a single piece containing the given
+    /// shapes, which are in page coordinates. Print-and-cut files get the
+    /// registration marks for `page`.
+    pub fn from_shapes_with_mode(
+        shapes: Vec<PathShape>,
+        mode: ConvertMode,
+        page: &PageSize,
+    ) -> FcmFile {
+        let mut file = FcmFile::from_shapes(shapes, mode.tool(), page);
+        if mode == ConvertMode::PrintAndCut {
+            file.to_print_and_cut(page);
+        }
+        file
+    }
+
+    /// Duplicate the existing pieces into a grid of `rows` by `cols`, with
+    /// `dx_fcm`/`dy_fcm` between neighbouring cells. The existing pieces form
+    /// the top-left cell; copies get new ids and are placed via their
+    /// transforms. Piece ids are 16 bit, so copying stops once they run out.
+    pub fn array(&mut self, rows: u32, cols: u32, dx_fcm: i32, dy_fcm: i32) {
+        let originals = self.piece_table.pieces.clone();
+        let mut next_id = originals
+            .iter()
+            .map(|(id, _)| *id as u32 + 1)
+            .max()
+            .unwrap_or(0);
+
+        for row in 0..rows {
+            for col in 0..cols {
+                if row == 0 && col == 0 {
+                    continue;
+                }
+                let offset_x = (col as i64 * dx_fcm as i64) as f32;
+                let offset_y = (row as i64 * dy_fcm as i64) as f32;
+                for (_, piece) in &originals {
+                    let Ok(id) = u16::try_from(next_id) else {
+                        return;
+                    };
+                    let mut copy = piece.clone();
+                    let (a, b, c, d, tx, ty) =
+                        copy.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+                    copy.transform = Some((a, b, c, d, tx + offset_x, ty + offset_y));
+                    self.piece_table.pieces.push((id, copy));
+                    next_id += 1;
+                }
+            }
+        }
+    }
+
+    /// Reverse the order the pieces are cut in. Each piece keeps its id.
+    pub fn reverse_piece_order(&mut self) {
+        self.piece_table.pieces.reverse();
+    }
+
+    /// Replace every Bezier outline in the file with a Line outline
+    /// approximating it within `tolerance_fcm` units, for machines that only
+    /// accept polylines
+    pub fn flatten_all(&mut self, tolerance_fcm: i32) {
+        for (_, piece) in &mut self.piece_table.pieces {
+            for shape in piece.paths.iter_mut().filter_map(|it| it.shape.as_mut()) {
+                shape.flatten(tolerance_fcm);
+            }
+        }
+    }
+
+    /// Keep only the paths whose tool includes all of `tool`'s flags, dropping
+    /// pieces left without paths. Used to split one design into separate
+    /// passes, e.g. a draw file and a cut file from the same source.
+    pub fn retain_tool(&mut self, tool: PathTool) {
+        for (_, piece) in &mut self.piece_table.pieces {
+            piece.paths.retain(|path| path.tool.contains(tool));
+        }
+        self.piece_table
+            .pieces
+            .retain(|(_, piece)| !piece.paths.is_empty());
+    }
+
+    /// Turn the file into a plain cut file (`#FCM`), dropping the registration
+    /// marks, for machines that don't accept print-and-cut files. The pieces
+    /// are unchanged.
+    pub fn to_cut_only(&mut self) {
+        self.file_header.variant = FileVariant::FCM;
+        self.file_header.print_to_cut = None;
+        self.cut_data.file_type = FileType::Cut;
+        self.cut_data.alignment = None;
+    }
+
+    /// Turn the file into a print-and-cut file (`#VCM`) for `page`, with the
+    /// registration marks at their standard positions. The cut area is set to
+    /// the page, as the marks are placed relative to it.
+    pub fn to_print_and_cut(&mut self, page: &PageSize) {
+        let (cut_width, cut_height) = page.to_fcm_units();
+        self.file_header.variant = FileVariant::VCM;
+        self.file_header.print_to_cut = Some(true);
+        self.cut_data.file_type = FileType::PrintAndCut;
+        self.cut_data.cut_width = cut_width;
+        self.cut_data.cut_height = cut_height;
+        self.cut_data.alignment = Some(AlignmentData {
+            needed: true,
+            marks: registration_marks::get_fcm_alignment_marks(page),
+        });
+    }
+
+    /// Scale all pieces by `factor` about the page origin, resizing their
+    /// paths and moving their positions. What happens to the cut area and the
+    /// registration marks depends on `marks`; the seam allowance and
+    /// rhinestone diameters keep their size.
+    pub fn scale(&mut self, factor: f64, marks: MarkScaling) {
+        let scale = |value: i32| (value as f64 * factor).round() as i32;
+        for (_, piece) in self.pieces_mut() {
+            for path in &mut piece.paths {
+                path.map_points(|p| Point {
+                    x: scale(p.x),
+                    y: scale(p.y),
+                });
+            }
+            if let Some((a, b, c, d, tx, ty)) = piece.transform {
+                let (tx, ty) = (tx as f64 * factor, ty as f64 * factor);
+                piece.transform = Some((a, b, c, d, tx as f32, ty as f32));
+            }
+            if let Some((min, max)) = piece.bounds() {
+                piece.width = (max.x - min.x) as u32;
+                piece.height = (max.y - min.y) as u32;
+            }
+        }
+
+        if marks == MarkScaling::Keep {
+            return;
+        }
+        let cut_data = &mut self.cut_data;
+        cut_data.cut_width = (cut_data.cut_width as f64 * factor).round() as u32;
+        cut_data.cut_height = (cut_data.cut_height as f64 * factor).round() as u32;
+        if let Some(alignment) = &mut cut_data.alignment {
+            if marks == MarkScaling::Scale {
+                for mark in &mut alignment.marks {
+                    *mark = Point {
+                        x: scale(mark.x),
+                        y: scale(mark.y),
+                    };
+                }
+            } else {
+                let page = PageSize::new(
+                    cut_data.cut_width as f64 / 100.0,
+                    cut_data.cut_height as f64 / 100.0,
+                );
+                alignment.marks = registration_marks::get_fcm_alignment_marks(&page);
+            }
+        }
+    }
+
+    /// Round every coordinate to the nearest multiple of `grid_fcm`, including
+    /// piece positions, so unrotated pieces end up on the grid in page
+    /// coordinates. Returns how many segments collapsed to zero length, which
+    /// happens when the grid is coarse compared to the detail of the shapes.
+    pub fn snap_to_grid(&mut self, grid_fcm: i32) -> usize {
+        let mut collapsed = 0;
+        for (_, piece) in &mut self.piece_table.pieces {
+            for path in &mut piece.paths {
+                if let Some(shape) = &mut path.shape {
+                    collapsed += shape.snap_to_grid(grid_fcm);
+                }
+                for rhinestone in &mut path.rhinestones {
+                    *rhinestone = geometry::snap_point(*rhinestone, grid_fcm);
+                }
+            }
+            if let Some((a, b, c, d, tx, ty)) = piece.transform {
+                let snap = |value: f32| geometry::snap(value.round() as i32, grid_fcm) as f32;
+                piece.transform = Some((a, b, c, d, snap(tx), snap(ty)));
+            }
+            if let Some((min, max)) = piece.bounds() {
+                piece.width = (max.x - min.x) as u32;
+                piece.height = (max.y - min.y) as u32;
+            }
+        }
+        collapsed
+    }
+
+    /// Whether the file was created in Canvas Workspace or on the machine,
+    /// going by the generator in the header
+    pub fn source(&self) -> FileSource {
+        FileSource::from_generator(self.file_header.generator)
+    }
+
+    /// Pieces with their ids, in file order
+    pub fn pieces(&self) -> impl Iterator<Item = (u16, &Piece)> {
+        self.piece_table
+            .pieces
+            .iter()
+            .map(|(id, piece)| (*id, piece))
+    }
+
+    /// Mutable pieces with their ids, in file order
+    pub fn pieces_mut(&mut self) -> impl Iterator<Item = (u16, &mut Piece)> {
+        self.piece_table
+            .pieces
+            .iter_mut()
+            .map(|(id, piece)| (*id, piece))
+    }
+
+    /// Id and label of every piece, in file order. Pieces without a label
+    /// have an empty string; labels are set with [`PieceBuilder::label`].
+    ///
+    /// [`PieceBuilder::label`]: crate::PieceBuilder::label
+    pub fn labels(&self) -> Vec<(u16, &str)> {
+        self.pieces()
+            .map(|(id, piece)| (id, piece.label.as_str()))
+            .collect()
+    }
+
+    /// Minimum and maximum corner of the box containing all pieces, in page coordinates
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        self.piece_table
+            .pieces
+            .iter()
+            .filter_map(|(_, piece)| piece.page_bounds())
+            .reduce(|(min_a, max_a), (min_b, max_b)| {
+                (
+                    Point {
+                        x: min_a.x.min(min_b.x),
+                        y: min_a.y.min(min_b.y),
+                    },
+                    Point {
+                        x: max_a.x.max(max_b.x),
+                        y: max_a.y.max(max_b.y),
+                    },
+                )
+            })
+    }
+
+    /// Combined bounds of all pieces in page coordinates, in mm, as
+    /// `(min_x, min_y, max_x, max_y)`
+    pub fn bounds_mm(&self) -> Option<(f64, f64, f64, f64)> {
+        self.bounds().map(|(min, max)| {
+            (
+                min.x as f64 / 100.0,
+                min.y as f64 / 100.0,
+                max.x as f64 / 100.0,
+                max.y as f64 / 100.0,
+            )
+        })
+    }
+
+    /// The cut paths as polylines in page coordinates in mm, for software
+    /// that only takes point lists. Curves are flattened within
+    /// `tolerance_mm` and piece transforms applied. Closed paths repeat their
+    /// first point at the end. Drawn paths and rhinestones are left out.
+    pub fn to_polylines_mm(&self, tolerance_mm: f64) -> Vec<Vec<(f64, f64)>> {
+        let tolerance = (tolerance_mm * 100.0).round() as i32;
+        let mut polylines = vec![];
+        for (_, piece) in self.pieces() {
+            let (a, b, c, d, tx, ty) = piece.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+            for path in &piece.paths {
+                if !path.tool.contains(PathTool::TOOL_CUT) {
+                    continue;
+                }
+                let Some(shape) = &path.shape else {
+                    continue;
+                };
+                let points = shape.to_polyline(tolerance);
+                polylines.push(
+                    points
+                        .into_iter()
+                        .map(|p| {
+                            let (x, y) = geometry::to_f64(p);
+                            (
+                                (a as f64 * x + c as f64 * y + tx as f64) / 100.0,
+                                (b as f64 * x + d as f64 * y + ty as f64) / 100.0,
+                            )
+                        })
+                        .collect(),
+                );
+            }
+        }
+        polylines
+    }
+
+    /// Total length of the paths in mm, by tool, to estimate cutting time and
+    /// wear on the blade or pen. Paths are keyed by their tool flags without
+    /// `PATH_OPEN`, so open and closed cut paths add up together. Curves are
+    /// flattened and piece transforms applied, so scaled pieces are measured
+    /// at their size on the page.
+    pub fn length_by_tool(&self) -> HashMap<PathTool, f64> {
+        let mut lengths = HashMap::new();
+        for (_, piece) in self.pieces() {
+            let (a, b, c, d, _, _) = piece.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+            for path in &piece.paths {
+                let Some(shape) = &path.shape else {
+                    continue;
+                };
+                let points = shape.to_polyline(geometry::DEFAULT_TOLERANCE);
+                let length: f64 = points
+                    .windows(2)
+                    .map(|w| {
+                        let (dx, dy) = ((w[1].x - w[0].x) as f64, (w[1].y - w[0].y) as f64);
+                        geometry::length(
+                            a as f64 * dx + c as f64 * dy,
+                            b as f64 * dx + d as f64 * dy,
+                        )
+                    })
+                    .sum();
+                *lengths
+                    .entry(path.tool.difference(PathTool::PATH_OPEN))
+                    .or_insert(0.0) += length / 100.0;
+            }
+        }
+        lengths
+    }
+
+    /// Area of material inside the closed cut paths in mm², to estimate how
+    /// much vinyl or paper a file uses. Holes are stored as separate paths,
+    /// so a contour lying inside an odd number of other contours of its piece
+    /// counts as a hole and is subtracted. Piece transforms are applied.
+    pub fn used_area_mm2(&self) -> f64 {
+        let mut area = 0.0;
+        for (_, piece) in self.pieces() {
+            let (a, b, c, d, _, _) = piece.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+            let scale = (a as f64 * d as f64 - b as f64 * c as f64).abs();
+            let contours = cut_contours(piece);
+            for (shape, depth) in contours.iter().zip(nesting_depths(&contours)) {
+                let sign = if depth % 2 == 0 { 1.0 } else { -1.0 };
+                area += sign * shape.signed_area().abs() * scale;
+            }
+        }
+        area / 10000.0
+    }
+
+    /// Add the seam allowance as cut paths, for machines that don't offset
+    /// the cut themselves. Every closed cut path that isn't a hole gets a cut
+    /// path `seam_allowance_width` units outside it with rounded corners, and
+    /// every hole one that far inside it, unless the hole closes up. The
+    /// original paths are kept. Piece sizes grow to match. The seam allowance
+    /// width is reset to zero so the machine doesn't add it again, which also
+    /// makes a second call do nothing.
+    pub fn materialize_seam_allowance(&mut self) {
+        let distance = self.cut_data.seam_allowance_width as i32;
+        if distance <= 0 {
+            return;
+        }
+        for (_, piece) in self.pieces_mut() {
+            let contours = cut_contours(piece);
+            let allowance: Vec<Path> = contours
+                .iter()
+                .zip(nesting_depths(&contours))
+                .flat_map(|(shape, depth)| {
+                    // Wind holes clockwise so the allowance shrinks them
+                    let is_hole = depth % 2 == 1;
+                    let mut contour = (*shape).clone();
+                    if is_hole == (contour.signed_area() > 0.0) {
+                        contour = contour.reversed();
+                    }
+                    contour
+                        .offset(distance, JoinStyle::Round)
+                        .into_iter()
+                        .filter(move |shape| (shape.signed_area() < 0.0) == is_hole)
+                })
+                .map(|shape| Path {
+                    tool: PathTool::TOOL_CUT,
+                    shape: Some(shape),
+                    rhinestone_diameter: None,
+                    rhinestones: vec![],
+                })
+                .collect();
+            piece.paths.extend(allowance);
+            if let Some((min, max)) = piece.bounds() {
+                piece.width = (max.x - min.x) as u32;
+                piece.height = (max.y - min.y) as u32;
+            }
+        }
+        self.cut_data.seam_allowance_width = 0;
+    }
+
+    /// Number of distinct points and of all points in the path shapes, as
+    /// `(unique, total)`, to spot redundant vertices. Points include the
+    /// start of each shape and Bezier control points. Points are compared
+    /// exactly in piece coordinates, within each piece.
+    pub fn unique_point_count(&self) -> (usize, usize) {
+        let mut unique = 0;
+        let mut total = 0;
+        for (_, piece) in self.pieces() {
+            let mut seen = std::collections::HashSet::new();
+            for shape in piece.paths.iter().filter_map(|path| path.shape.as_ref()) {
+                let points = std::iter::once(shape.start)
+                    .chain(shape.outlines.iter().flat_map(Outline::points));
+                for point in points {
+                    seen.insert((point.x, point.y));
+                    total += 1;
+                }
+            }
+            unique += seen.len();
+        }
+        (unique, total)
+    }
+
+    /// Indices of paths with detail smaller than `min_mm`, which can't be cut
+    /// cleanly. Paths are numbered across all pieces in order, counting paths
+    /// without a shape. A closed path is flagged when its bounds are narrower
+    /// than `min_mm` or two parts of it, like the sides of a slot or spike,
+    /// come closer than that; an open path when it is shorter than `min_mm`
+    /// on both axes or doubles back that closely. Piece transforms are
+    /// applied, so scaled pieces are checked at their size on the page.
+    pub fn check_min_feature_size(&self, min_mm: f64) -> Vec<usize> {
+        let min = min_mm * 100.0;
+        if min <= 0.0 {
+            return vec![];
+        }
+
+        let mut flagged = vec![];
+        let mut index = 0;
+        for (_, piece) in self.pieces() {
+            let (a, b, c, d, _, _) = piece.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+            for path in &piece.paths {
+                index += 1;
+                let Some(shape) = &path.shape else {
+                    continue;
+                };
+                let points: Vec<(f64, f64)> = shape
+                    .to_polyline(geometry::DEFAULT_TOLERANCE)
+                    .into_iter()
+                    .map(|p| {
+                        let (x, y) = geometry::to_f64(p);
+                        (a as f64 * x + c as f64 * y, b as f64 * x + d as f64 * y)
+                    })
+                    .collect();
+                let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+                let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+                for &(x, y) in &points {
+                    (min_x, min_y) = (min_x.min(x), min_y.min(y));
+                    (max_x, max_y) = (max_x.max(x), max_y.max(y));
+                }
+                let (width, height) = (max_x - min_x, max_y - min_y);
+
+                let closed = shape.is_closed();
+                let too_small = if closed {
+                    width.min(height) < min
+                } else {
+                    width.max(height) < min
+                };
+                if too_small || geometry::has_narrow_gap(&points, closed, min) {
+                    flagged.push(index - 1);
+                }
+            }
+        }
+        flagged
+    }
+
+    /// Number of closed contours that will be cut across all pieces. Each path
+    /// is one contour, so the outline of a shape and each of its holes count
+    /// separately. Open paths and rhinestones don't count.
+    pub fn closed_region_count(&self) -> usize {
+        self.pieces()
+            .flat_map(|(_, piece)| &piece.paths)
+            .filter(|path| path.shape.as_ref().is_some_and(PathShape::is_closed))
+            .count()
+    }
+
+    /// Describe every piece that doesn't fit on `page`, either extending past
+    /// its edges or into the `margin_fcm` the machine can't reach. An empty
+    /// list means all pieces can be cut.
+    pub fn fit_warnings(&self, page: &PageSize, margin_fcm: i32) -> Vec<String> {
+        let (width, height) = page.to_fcm_units();
+        let (cut_min, cut_max) = page.cuttable_area(margin_fcm);
+        let inside = |min: Point, max: Point, area_min: Point, area_max: Point| {
+            min.x >= area_min.x && min.y >= area_min.y && max.x <= area_max.x && max.y <= area_max.y
+        };
+
+        let mut warnings = Vec::new();
+        for (id, piece) in self.pieces() {
+            let Some((min, max)) = piece.page_bounds() else {
+                continue;
+            };
+            let page_max = Point {
+                x: width as i32,
+                y: height as i32,
+            };
+            if !inside(min, max, Point::default(), page_max) {
+                warnings.push(format!("Piece {} extends past the page", id));
+            } else if !inside(min, max, cut_min, cut_max) {
+                warnings.push(format!(
+                    "Piece {} extends into the {}mm margin outside the cuttable area",
+                    id,
+                    margin_fcm as f64 / 100.0
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// Mirror all pieces vertically about a page of `page_height_fcm`, for
+    /// machines with the origin at the bottom left. Pieces keep their
+    /// rotation and scale, mirrored to match. Registration marks of
+    /// print-and-cut files are mirrored along with the pieces.
+    pub fn flip_y(&mut self, page_height_fcm: i32) {
+        for (_, piece) in self.pieces_mut() {
+            for path in &mut piece.paths {
+                path.map_points(|p| Point { x: p.x, y: -p.y });
+            }
+            let (a, b, c, d, tx, ty) = piece.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+            piece.transform = Some((a, -b, -c, d, tx, page_height_fcm as f32 - ty));
+        }
+        if let Some(alignment) = &mut self.cut_data.alignment {
+            for mark in &mut alignment.marks {
+                mark.y = page_height_fcm - mark.y;
+            }
+        }
+    }
+
+    /// Move all pieces together so the top-left corner of their combined
+    /// bounds sits at `(margin_fcm, margin_fcm)`, keeping their relative
+    /// positions
+    pub fn recenter_to(&mut self, margin_fcm: i32) {
+        if let Some((min, _)) = self.bounds() {
+            let (dx, dy) = (margin_fcm - min.x, margin_fcm - min.y);
+            for (_, piece) in &mut self.piece_table.pieces {
+                piece.translate(dx, dy);
+            }
+        }
+    }
+
+    /// Shrink or grow the cut area to end `margin_fcm` past the content, for
+    /// machines that only feed as much of the mat as the cut area needs. The
+    /// cut area starts at the page origin, so call
+    /// [`recenter_to`](FcmFile::recenter_to) first to move the content into
+    /// the top left corner. Files without paths are left unchanged. The
+    /// registration marks of print-and-cut files are placed relative to the
+    /// cut area, so this is meant for plain cut files.
+    pub fn fit_cut_area(&mut self, margin_fcm: i32) {
+        if let Some((_, max)) = self.bounds() {
+            self.cut_data.cut_width = (max.x + margin_fcm).max(0) as u32;
+            self.cut_data.cut_height = (max.y + margin_fcm).max(0) as u32;
+        }
+    }
+
+    /// JSON representation of the pieces, paths and points in mm, for quick
+    /// previews. Tools are written as their raw flag bits. This is not meant
+    /// to be read back.
+    pub fn to_json(&self) -> String {
+        ToJson::to_json(self)
+    }
+
+    /// Check the file for problems that would make it fail to encode, or that
+    /// files written by Canvas Workspace never have. Draw-only files are valid
+    /// and use [`FileType::Cut`] like any other file without print and cut.
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |message: String| Error {
+            message: format!("Invalid file: {0}", message),
+        };
+
+        if let FileVariant::Unknown(raw) = self.file_header.variant {
+            return Err(invalid(format!("unknown variant {:?}", raw)));
+        }
+        if self.file_header.version.len() != 4 {
+            return Err(invalid(format!(
+                "version must be 4 bytes, got {:?}",
+                self.file_header.version
+            )));
+        }
+        match (self.cut_data.file_type, &self.cut_data.alignment) {
+            (FileType::Unknown(raw), _) => {
+                return Err(invalid(format!("unknown file type {:#x}", raw)))
+            }
+            (FileType::PrintAndCut, None) => {
+                return Err(invalid(String::from(
+                    "print and cut file without alignment data",
+                )))
+            }
+            (FileType::Cut, Some(_)) => {
+                return Err(invalid(String::from("cut file with alignment data")))
+            }
+            _ => {}
+        }
+        if let Some(alignment) = &self.cut_data.alignment {
+            // The cut area of a print-and-cut file is the printed page
+            let page = PageSize::new(
+                self.cut_data.cut_width as f64 / 100.0,
+                self.cut_data.cut_height as f64 / 100.0,
+            );
+            if let Err(marks) = alignment.validate_against(&page) {
+                return Err(invalid(format!(
+                    "registration marks {:?} are off the page or overlap",
+                    marks
+                )));
+            }
+        }
+
+        let mut ids = std::collections::HashSet::new();
+        for (id, piece) in self.pieces() {
+            if !ids.insert(id) {
+                return Err(invalid(format!("duplicate piece id {}", id)));
+            }
+            if !piece.label.is_empty() && piece.label.len() != 3 {
+                return Err(invalid(format!(
+                    "piece {} label must be 3 bytes, got {:?}",
+                    id, piece.label
+                )));
+            }
+            if let Some((a, b, c, d, tx, ty)) = piece.transform {
+                if ![a, b, c, d, tx, ty].iter().all(|value| value.is_finite()) {
+                    return Err(invalid(format!("piece {} transform is not finite", id)));
+                }
+            }
+            for (index, path) in piece.paths.iter().enumerate() {
+                let Some(shape) = &path.shape else {
+                    continue;
+                };
+                let (min, max) = shape.bounds();
+                if min.x == max.x && min.y == max.y {
+                    return Err(invalid(format!(
+                        "piece {} path {} has no extent",
+                        id, index
+                    )));
+                }
+                // Casting an infinite float to i32 saturates to the limits
+                if [min.x, min.y].contains(&i32::MIN) || [max.x, max.y].contains(&i32::MAX) {
+                    return Err(invalid(format!(
+                        "piece {} path {} has coordinates out of range",
+                        id, index
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         self.encode_to_vec().map_err(|e| Error {
             message: format!("Could not serialize file: {0}", e),
         })
     }
 
+    /// Length in bytes of the serialized file, e.g. to check an upload limit
+    /// before writing. Fails in the same cases as [`FcmFile::to_bytes`].
+    pub fn serialized_size(&self) -> Result<usize, Error> {
+        // Pieces are encoded separately to compute the offset table, so the
+        // size can't be known without encoding them
+        Ok(self.to_bytes()?.len())
+    }
+
+    /// 64-bit FNV-1a hash of the serialized file, stable across builds and
+    /// platforms so it can be used as a cache key
+    pub fn content_hash(&self) -> Result<u64, Error> {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in self.to_bytes()? {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        Ok(hash)
+    }
+
     pub fn to_file<T: AsRef<std::path::Path>>(&self, file: T) -> Result<(), Error> {
         fs::write(file, self.to_bytes()?.as_slice()).map_err(|e| Error {
             message: format!("Could not write to file: {0}", e),
@@ -46,17 +794,45 @@ impl FcmFile {
     }
 }
 
+/// Closed cut paths of a piece, the contours that are cut out
+fn cut_contours(piece: &Piece) -> Vec<&PathShape> {
+    piece
+        .paths
+        .iter()
+        .filter(|path| path.tool.contains(PathTool::TOOL_CUT))
+        .filter_map(|path| path.shape.as_ref())
+        .filter(|shape| shape.is_closed())
+        .collect()
+}
+
+/// Number of other contours each contour lies inside, odd for holes
+fn nesting_depths(contours: &[&PathShape]) -> Vec<usize> {
+    contours
+        .iter()
+        .enumerate()
+        .map(|(i, shape)| {
+            contours
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| *j != i && other.contains_point(shape.start))
+                .count()
+        })
+        .collect()
+}
+
 pub(crate) fn read_fcm_file(input: &[u8]) -> IResult<&[u8], FcmFile> {
     map(
         tuple((
             file_header::read_file_header,
             cut_data::read_cut_data,
             piece_table::read_piece_table,
+            rest,
         )),
-        |(file_header, cut_data, piece_table)| FcmFile {
+        |(file_header, cut_data, piece_table, trailer)| FcmFile {
             file_header,
             cut_data,
             piece_table,
+            trailer: trailer.to_vec(),
         },
     )(input)
 }
@@ -66,6 +842,919 @@ impl Encode for FcmFile {
         self.file_header.encode(buffer)?;
         self.cut_data.encode(buffer)?;
         self.piece_table.encode(buffer)?;
+        buffer.write_all(&self.trailer)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SegmentLine;
+
+    fn square(x: i32, y: i32, size: i32) -> PathShape {
+        let corner = |x, y| SegmentLine {
+            end: Point { x, y },
+        };
+        PathShape {
+            start: Point { x, y },
+            outlines: vec![Outline::Line(vec![
+                corner(x + size, y),
+                corner(x + size, y + size),
+                corner(x, y + size),
+                corner(x, y),
+            ])],
+        }
+    }
+
+    #[test]
+    fn test_from_shapes() {
+        let shapes = vec![square(1000, 1000, 500), square(2000, 1000, 1000)];
+        let file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::SQUARE_12);
+
+        let bytes = file.to_bytes().unwrap();
+        let parsed = FcmFile::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+
+        assert_eq!(parsed.cut_data.file_type, FileType::Cut);
+        assert_eq!(parsed.piece_table.pieces.len(), 1);
+        let (_, piece) = &parsed.piece_table.pieces[0];
+        assert_eq!(piece.paths.len(), 2);
+        assert_eq!((piece.width, piece.height), (2000, 1000));
+        assert_eq!(piece.transform, Some((1.0, 0.0, 0.0, 1.0, 2000.0, 1500.0)));
+        assert_eq!(piece.paths[0].shape.as_ref().unwrap().start.x, -1000);
+    }
+
+    #[test]
+    fn test_from_shapes_with_mode() {
+        for (mode, variant, file_type, tool) in [
+            (
+                ConvertMode::CutOnly,
+                FileVariant::FCM,
+                FileType::Cut,
+                PathTool::TOOL_CUT,
+            ),
+            (
+                ConvertMode::PrintAndCut,
+                FileVariant::VCM,
+                FileType::PrintAndCut,
+                PathTool::TOOL_CUT,
+            ),
+            (
+                ConvertMode::DrawAndCut,
+                FileVariant::FCM,
+                FileType::Cut,
+                PathTool::TOOL_CUT | PathTool::TOOL_DRAW,
+            ),
+        ] {
+            let file =
+                FcmFile::from_shapes_with_mode(vec![square(2000, 3000, 500)], mode, &PageSize::A4);
+            let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+            parsed.validate().unwrap();
+
+            assert_eq!(parsed.file_header.variant, variant);
+            assert_eq!(parsed.cut_data.file_type, file_type);
+            assert_eq!(
+                parsed.cut_data.needs_scanning(),
+                mode == ConvertMode::PrintAndCut
+            );
+            assert_eq!(parsed.piece_table.pieces[0].1.paths[0].tool, tool);
+        }
+    }
+
+    #[test]
+    fn test_byte_swapped_cut_size() {
+        let mut file = FcmFile::from_shapes(
+            vec![square(1000, 1000, 500)],
+            PathTool::TOOL_CUT,
+            &PageSize::A4,
+        );
+        file.cut_data.cut_width = file.cut_data.cut_width.swap_bytes();
+        file.cut_data.cut_height = file.cut_data.cut_height.swap_bytes();
+        let bytes = file.to_bytes().unwrap();
+
+        let error = FcmFile::from_bytes(&bytes).unwrap_err();
+        assert!(error.to_string().contains("byte order"), "{}", error);
+        assert!(FcmFile::read_metadata(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_check_min_feature_size() {
+        let polyline = |points: &[(i32, i32)], closed| {
+            let points: Vec<Point> = points.iter().map(|&(x, y)| Point { x, y }).collect();
+            PathShape::from_fcm_points(&points, closed)
+        };
+        // A 20mm square with a 0.5mm wide, 5mm deep slot cut into its top
+        let slotted = polyline(
+            &[
+                (0, 0),
+                (975, 0),
+                (975, 500),
+                (1025, 500),
+                (1025, 0),
+                (2000, 0),
+                (2000, 2000),
+                (0, 2000),
+            ],
+            true,
+        );
+        let shapes = vec![
+            square(5000, 5000, 2000),
+            slotted,
+            square(8000, 8000, 50),
+            polyline(&[(0, 5000), (1000, 5000)], false),
+        ];
+        let file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+
+        assert_eq!(file.check_min_feature_size(1.0), vec![1, 2]);
+        assert_eq!(file.check_min_feature_size(0.25), Vec::<usize>::new());
+        assert_eq!(file.check_min_feature_size(15.0), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_scale_marks() {
+        let shapes = vec![square(5000, 5000, 2000)];
+        let original =
+            FcmFile::from_shapes_with_mode(shapes, ConvertMode::PrintAndCut, &PageSize::A4);
+        let marks = |file: &FcmFile| -> Vec<(i32, i32)> {
+            let alignment = file.cut_data.alignment.as_ref().unwrap();
+            alignment.marks.iter().map(|p| (p.x, p.y)).collect()
+        };
+        let cut_area = |file: &FcmFile| (file.cut_data.cut_width, file.cut_data.cut_height);
+
+        for mode in [
+            MarkScaling::Keep,
+            MarkScaling::Scale,
+            MarkScaling::Recompute,
+        ] {
+            let mut file = FcmFile::from_bytes(&original.to_bytes().unwrap()).unwrap();
+            file.scale(0.5, mode);
+            file.validate().unwrap();
+            let (min, max) = file.bounds().unwrap();
+            assert_eq!((min.x, min.y, max.x, max.y), (2500, 2500, 3500, 3500));
+            let piece = &file.piece_table.pieces[0].1;
+            assert_eq!((piece.width, piece.height), (1000, 1000));
+
+            match mode {
+                MarkScaling::Keep => {
+                    assert_eq!(cut_area(&file), cut_area(&original));
+                    assert_eq!(marks(&file), marks(&original));
+                }
+                MarkScaling::Scale => {
+                    assert_eq!(cut_area(&file), (10500, 14850));
+                    let halved: Vec<_> = marks(&original)
+                        .iter()
+                        .map(|&(x, y)| {
+                            (
+                                (x as f64 / 2.0).round() as i32,
+                                (y as f64 / 2.0).round() as i32,
+                            )
+                        })
+                        .collect();
+                    assert_eq!(marks(&file), halved);
+                }
+                MarkScaling::Recompute => {
+                    assert_eq!(cut_area(&file), (10500, 14850));
+                    let page = PageSize::new(105.0, 148.5);
+                    let standard = registration_marks::get_fcm_alignment_marks(&page);
+                    let standard: Vec<_> = standard.iter().map(|p| (p.x, p.y)).collect();
+                    assert_eq!(marks(&file), standard);
+                    assert_ne!(marks(&file), marks(&original));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_polylines_mm() {
+        let shapes = vec![
+            square(1000, 2000, 500),
+            PathShape::from_fcm_points(&[Point { x: 0, y: 0 }, Point { x: 100, y: 0 }], false),
+        ];
+        let mut file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+        file.piece_table.pieces[0].1.paths[1].tool = PathTool::TOOL_DRAW | PathTool::PATH_OPEN;
+
+        let polylines = file.to_polylines_mm(0.05);
+        assert_eq!(polylines.len(), 1);
+        assert_eq!(polylines[0].len(), 5);
+        assert_eq!(polylines[0][0], polylines[0][4]);
+        let (min_x, min_y) = polylines[0]
+            .iter()
+            .fold((f64::MAX, f64::MAX), |(x, y), p| (x.min(p.0), y.min(p.1)));
+        assert_eq!((min_x, min_y), (10.0, 20.0));
+
+        // Rotated a quarter turn about the piece center
+        let (_, piece) = &mut file.piece_table.pieces[0];
+        let (.., tx, ty) = piece.transform.unwrap();
+        piece.transform = Some((0.0, 1.0, -1.0, 0.0, tx, ty));
+        let corners = &file.to_polylines_mm(0.05)[0];
+        let start = file.piece_table.pieces[0].1.paths[0]
+            .shape
+            .as_ref()
+            .unwrap()
+            .start;
+        let expected = (
+            (-start.y as f64 + tx as f64) / 100.0,
+            (start.x as f64 + ty as f64) / 100.0,
+        );
+        assert_eq!(corners[0], expected);
+    }
+
+    #[test]
+    fn test_validate_marks() {
+        let shapes = vec![square(5000, 5000, 2000)];
+        let mut file =
+            FcmFile::from_shapes_with_mode(shapes, ConvertMode::PrintAndCut, &PageSize::A4);
+        file.validate().unwrap();
+
+        file.cut_data.alignment.as_mut().unwrap().marks[3].y = 30000;
+        let error = file.validate().unwrap_err();
+        assert!(
+            error.to_string().contains("registration marks [3]"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_fit_cut_area() {
+        // A 20mm square in the middle of an A4 page
+        let shapes = vec![square(9500, 13850, 2000)];
+        let mut file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+        assert_eq!(
+            (file.cut_data.cut_width, file.cut_data.cut_height),
+            (21000, 29700)
+        );
+
+        file.fit_cut_area(1000);
+        assert_eq!(
+            (file.cut_data.cut_width, file.cut_data.cut_height),
+            (12500, 16850)
+        );
+
+        file.recenter_to(1000);
+        file.fit_cut_area(1000);
+        assert_eq!(
+            (file.cut_data.cut_width, file.cut_data.cut_height),
+            (4000, 4000)
+        );
+        file.validate().unwrap();
+
+        let mut empty = FcmFile::from_shapes(vec![], PathTool::TOOL_CUT, &PageSize::A4);
+        empty.fit_cut_area(1000);
+        assert_eq!(empty.cut_data.cut_width, 21000);
+    }
+
+    #[test]
+    fn test_smallest_cut_file() {
+        let line = PathShape {
+            start: Point { x: 0, y: 0 },
+            outlines: vec![Outline::Line(vec![SegmentLine {
+                end: Point { x: 100, y: 0 },
+            }])],
+        };
+        let file = FcmFile::from_shapes(
+            vec![line],
+            PathTool::TOOL_CUT | PathTool::PATH_OPEN,
+            &PageSize::A4,
+        );
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(
+            FcmFile::from_bytes(&bytes).unwrap().to_bytes().unwrap(),
+            bytes
+        );
+
+        let dot = PathShape {
+            start: Point { x: 50, y: 50 },
+            outlines: vec![Outline::Line(vec![SegmentLine {
+                end: Point { x: 50, y: 50 },
+            }])],
+        };
+        let file = FcmFile::from_shapes(vec![dot], PathTool::TOOL_CUT, &PageSize::A4);
+        assert!(file.validate().is_err());
+        // Still written as it is, so such files round-trip
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(
+            FcmFile::from_bytes(&bytes).unwrap().to_bytes().unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_retain_tool() {
+        let shapes = vec![square(0, 0, 500), square(1000, 0, 500)];
+        let mut file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+        file.array(1, 2, 2000, 0);
+        file.piece_table.pieces[0].1.paths[0].tool = PathTool::TOOL_DRAW;
+        for path in &mut file.piece_table.pieces[1].1.paths {
+            path.tool = PathTool::TOOL_DRAW;
+        }
+
+        file.retain_tool(PathTool::TOOL_CUT);
+        assert_eq!(file.piece_table.pieces.len(), 1);
+        let (_, piece) = &file.piece_table.pieces[0];
+        assert_eq!(piece.paths.len(), 1);
+        assert_eq!(piece.paths[0].tool, PathTool::TOOL_CUT);
+    }
+
+    #[test]
+    fn test_print_and_cut_conversion() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        let cut_bytes = file.to_bytes().unwrap();
+
+        file.to_print_and_cut(&PageSize::A4);
+        let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        parsed.validate().unwrap();
+        assert_eq!(parsed.file_header.variant, FileVariant::VCM);
+        assert_eq!(parsed.file_header.print_to_cut, Some(true));
+        assert_eq!(parsed.cut_data.file_type, FileType::PrintAndCut);
+        assert_eq!(parsed.cut_data.alignment.as_ref().unwrap().marks.len(), 4);
+        assert!(parsed.cut_data.needs_scanning());
+
+        file.to_cut_only();
+        let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        parsed.validate().unwrap();
+        assert_eq!(parsed.file_header.variant, FileVariant::FCM);
+        assert!(parsed.cut_data.alignment.is_none());
+        assert_eq!(file.to_bytes().unwrap(), cut_bytes);
+    }
+
+    #[test]
+    fn test_two_alignment_marks() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.to_print_and_cut(&PageSize::A4);
+        let alignment = file.cut_data.alignment.as_mut().unwrap();
+        alignment.marks = vec![Point { x: 1200, y: 1398 }, Point { x: 19800, y: 28302 }];
+
+        let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        parsed.validate().unwrap();
+        let marks: Vec<_> = parsed
+            .cut_data
+            .alignment
+            .as_ref()
+            .unwrap()
+            .marks
+            .iter()
+            .map(|p| (p.x, p.y))
+            .collect();
+        assert_eq!(marks, vec![(1200, 1398), (19800, 28302)]);
+        assert!(parsed.cut_data.is_print_and_cut());
+    }
+
+    #[test]
+    fn test_serialized_size() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.array(2, 2, 1000, 1000);
+        file.trailer = vec![0; 7];
+
+        assert_eq!(
+            file.serialized_size().unwrap(),
+            file.to_bytes().unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_single_point_shape() {
+        let dot = PathShape {
+            start: Point { x: 500, y: 700 },
+            outlines: vec![Outline::Line(vec![SegmentLine {
+                end: Point { x: 500, y: 700 },
+            }])],
+        };
+        let mut file = FcmFile::from_shapes(vec![dot], PathTool::TOOL_CUT, &PageSize::A4);
+
+        let (_, piece) = &file.piece_table.pieces[0];
+        assert_eq!((piece.width, piece.height), (0, 0));
+        assert_eq!(piece.transform, Some((1.0, 0.0, 0.0, 1.0, 500.0, 700.0)));
+        // The thumbnail shows a single dot in the middle
+        let preview = thumbnail::decode(&thumbnail::render_piece(piece, 40, 30)).unwrap();
+        let black: Vec<usize> = (0..preview.pixels.len())
+            .filter(|&i| preview.pixels[i])
+            .collect();
+        assert_eq!(black, vec![15 * 40 + 20]);
+        assert!(file.fit_warnings(&PageSize::A4, 500).is_empty());
+        assert!(!file.to_json().contains("NaN") && !file.to_json().contains("inf"));
+
+        file.recenter_to(1000);
+        file.flatten_all(0);
+        file.snap_to_grid(0);
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (1000, 1000, 1000, 1000));
+
+        // There is nothing to cut, so the file isn't valid
+        assert!(file.validate().is_err());
+    }
+
+    #[test]
+    fn test_trailer_round_trip() {
+        let file = FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        let mut bytes = file.to_bytes().unwrap();
+        bytes.extend_from_slice(b"\x00\x00FOOTER");
+
+        let parsed = FcmFile::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.trailer, b"\x00\x00FOOTER");
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_read_metadata() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.array(1, 3, 1000, 0);
+        let bytes = file.to_bytes().unwrap();
+
+        let full = FcmFile::from_bytes(&bytes).unwrap();
+        let metadata = FcmFile::read_metadata(&bytes).unwrap();
+        assert_eq!(metadata.piece_count as usize, full.piece_table.pieces.len());
+        assert_eq!(metadata.piece_count, 3);
+        assert_eq!(metadata.file_header.variant, full.file_header.variant);
+        assert_eq!(metadata.file_header.content_id, full.file_header.content_id);
+        assert_eq!(metadata.cut_data.file_type, full.cut_data.file_type);
+        assert_eq!(metadata.cut_data.cut_width, full.cut_data.cut_width);
+
+        // Geometry isn't needed, so a cut-off piece table still reads
+        assert!(FcmFile::read_metadata(&bytes[..bytes.len() - 10]).is_ok());
+    }
+
+    #[test]
+    fn test_large_transform_round_trip() {
+        let shapes = vec![square(5_000_001, 7_000_003, 1001)];
+        let file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+        let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+
+        let (min, max) = parsed.bounds().unwrap();
+        assert!((min.x - 5_000_001).abs() <= 1 && (min.y - 7_000_003).abs() <= 1);
+        assert!((max.x - 5_001_002).abs() <= 1 && (max.y - 7_001_004).abs() <= 1);
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.array(1, 2, 1000, 0);
+        for ((_, piece), label) in file.pieces_mut().zip(["A01", "B02"]) {
+            piece.label = String::from(label);
+        }
+        assert_eq!(file.labels(), vec![(0, "A01"), (1, "B02")]);
+
+        let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        assert_eq!(parsed.labels(), vec![(0, "A01"), (1, "B02")]);
+    }
+
+    #[test]
+    fn test_reverse_piece_order() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.array(1, 3, 1000, 0);
+        let centers = |file: &FcmFile| -> Vec<(u16, f32)> {
+            file.pieces()
+                .map(|(id, piece)| (id, piece.transform.unwrap().4))
+                .collect()
+        };
+        assert_eq!(centers(&file), vec![(0, 250.0), (1, 1250.0), (2, 2250.0)]);
+
+        file.reverse_piece_order();
+        assert_eq!(centers(&file), vec![(2, 2250.0), (1, 1250.0), (0, 250.0)]);
+
+        let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        assert_eq!(centers(&parsed), centers(&file));
+    }
+
+    #[test]
+    fn test_used_area_mm2() {
+        let file =
+            FcmFile::from_shapes(vec![square(0, 0, 10000)], PathTool::TOOL_CUT, &PageSize::A4);
+        assert!((file.used_area_mm2() - 10000.0).abs() < 1e-6);
+
+        // A 20mm square hole, stored as its own path, with a 10mm island in it
+        let file = FcmFile::from_shapes(
+            vec![
+                square(0, 0, 10000),
+                square(4000, 4000, 2000),
+                square(4500, 4500, 1000),
+            ],
+            PathTool::TOOL_CUT,
+            &PageSize::A4,
+        );
+        assert!((file.used_area_mm2() - (10000.0 - 400.0 + 100.0)).abs() < 1e-6);
+
+        // Drawn paths and other pieces scaled by their transform
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 1000)], PathTool::TOOL_DRAW, &PageSize::A4);
+        assert_eq!(file.used_area_mm2(), 0.0);
+        for (_, piece) in file.pieces_mut() {
+            piece.paths[0].tool = PathTool::TOOL_CUT;
+            piece.transform = Some((2.0, 0.0, 0.0, 3.0, 0.0, 0.0));
+        }
+        assert!((file.used_area_mm2() - 600.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_materialize_seam_allowance() {
+        // A 20mm square with a 10mm hole and a 7mm allowance
+        let mut file = FcmFile::from_shapes(
+            vec![square(0, 0, 2000), square(500, 500, 1000)],
+            PathTool::TOOL_CUT,
+            &PageSize::A4,
+        );
+        file.cut_data.seam_allowance_width = 700;
+        file.materialize_seam_allowance();
+
+        let (_, piece) = file.pieces().next().unwrap();
+        assert_eq!(piece.paths.len(), 3);
+        let (min, max) = piece.paths[2].shape.as_ref().unwrap().bounds();
+        assert_eq!((min.x, min.y, max.x, max.y), (-1700, -1700, 1700, 1700));
+        assert_eq!((piece.width, piece.height), (3400, 3400));
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (-700, -700, 2700, 2700));
+        file.validate().unwrap();
+
+        // The allowance is now part of the paths, so a second call does nothing
+        assert_eq!(file.cut_data.seam_allowance_width, 0);
+        let bytes = file.to_bytes().unwrap();
+        file.materialize_seam_allowance();
+        assert_eq!(file.to_bytes().unwrap(), bytes);
+
+        // A 30mm square with a 20mm hole, which shrinks by the allowance
+        let mut file = FcmFile::from_shapes(
+            vec![square(0, 0, 3000), square(500, 500, 2000)],
+            PathTool::TOOL_CUT,
+            &PageSize::A4,
+        );
+        file.cut_data.seam_allowance_width = 300;
+        file.materialize_seam_allowance();
+        let (_, piece) = file.pieces().next().unwrap();
+        assert_eq!(piece.paths.len(), 4);
+        let hole = piece.paths[3].shape.as_ref().unwrap();
+        let (min, max) = hole.bounds();
+        assert_eq!((min.x, min.y, max.x, max.y), (-700, -700, 700, 700));
+    }
+
+    #[test]
+    fn test_unique_point_count() {
+        let file = FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        assert_eq!(file.unique_point_count(), (4, 5));
+
+        // Repeated corners, as left behind by boolean operations
+        let corner = |x, y| SegmentLine {
+            end: Point { x, y },
+        };
+        let mut shape = square(0, 0, 500);
+        shape.outlines.push(Outline::Line(vec![
+            corner(0, 0),
+            corner(500, 0),
+            corner(500, 0),
+        ]));
+        let file = FcmFile::from_shapes(vec![shape], PathTool::TOOL_CUT, &PageSize::A4);
+        assert_eq!(file.unique_point_count(), (4, 8));
+    }
+
+    #[test]
+    fn test_draw_only() {
+        let file = FcmFile::from_shapes(
+            vec![square(0, 0, 1000), square(2000, 0, 500)],
+            PathTool::TOOL_DRAW,
+            &PageSize::A4,
+        );
+        file.validate().unwrap();
+        assert_eq!(file.cut_data.file_type, FileType::Cut);
+
+        let bytes = file.to_bytes().unwrap();
+        let parsed = FcmFile::from_bytes(&bytes).unwrap();
+        parsed.validate().unwrap();
+        assert!(parsed
+            .pieces()
+            .flat_map(|(_, piece)| &piece.paths)
+            .all(|path| path.tool == PathTool::TOOL_DRAW));
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 1000)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.validate().unwrap();
+
+        file.piece_table.pieces[0].1.label = String::from("AB");
+        assert!(file.validate().is_err());
+        file.piece_table.pieces[0].1.label = String::from("ABC");
+        file.validate().unwrap();
+
+        file.cut_data.file_type = FileType::PrintAndCut;
+        assert!(file.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_non_finite() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 1000)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.piece_table.pieces[0].1.transform = Some((1.0, 0.0, 0.0, 1.0, f32::NAN, 0.0));
+        assert!(file.validate().is_err());
+
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 1000)], PathTool::TOOL_CUT, &PageSize::A4);
+        let shape = file.piece_table.pieces[0].1.paths[0]
+            .shape
+            .as_mut()
+            .unwrap();
+        shape.start.x = f64::INFINITY as i32;
+        assert!(file.validate().is_err());
+    }
+
+    #[test]
+    fn test_array() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.array(2, 3, 1000, 2000);
+
+        let pieces = &file.piece_table.pieces;
+        let ids: Vec<u16> = pieces.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![0, 1, 2, 3, 4, 5]);
+
+        let positions: Vec<(f32, f32)> = pieces
+            .iter()
+            .map(|(_, piece)| {
+                let (_, _, _, _, tx, ty) = piece.transform.unwrap();
+                (tx, ty)
+            })
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                (250.0, 250.0),
+                (1250.0, 250.0),
+                (2250.0, 250.0),
+                (250.0, 2250.0),
+                (1250.0, 2250.0),
+                (2250.0, 2250.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_limits() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.piece_table.pieces[0].0 = u16::MAX - 2;
+        file.array(1, 5, i32::MAX, 0);
+
+        // Only two ids are left after the original
+        let pieces = &file.piece_table.pieces;
+        let ids: Vec<u16> = pieces.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![u16::MAX - 2, u16::MAX - 1, u16::MAX]);
+        let (_, _, _, _, tx, _) = pieces[2].1.transform.unwrap();
+        assert_eq!(tx, 250.0 + 2.0 * i32::MAX as f32);
+    }
+
+    #[test]
+    fn test_recenter_to() {
+        let mut file = FcmFile::from_shapes(
+            vec![square(5000, 7000, 500), square(9000, 6000, 1000)],
+            PathTool::TOOL_CUT,
+            &PageSize::A4,
+        );
+        file.array(2, 2, 3000, 3000);
+        file.recenter_to(1000);
+
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.x, min.y), (1000, 1000));
+        assert_eq!((max.x, max.y), (1000 + 8000, 1000 + 4500));
+    }
+
+    #[test]
+    fn test_bounds_mm() {
+        let mut file = FcmFile::from_shapes(
+            vec![square(1000, 2000, 500)],
+            PathTool::TOOL_CUT,
+            &PageSize::A4,
+        );
+        file.array(1, 2, 4050, 0);
+        assert_eq!(file.bounds_mm(), Some((10.0, 20.0, 55.5, 25.0)));
+
+        file.piece_table.pieces.clear();
+        assert_eq!(file.bounds_mm(), None);
+    }
+
+    #[test]
+    fn test_length_by_tool() {
+        let line = PathShape {
+            start: Point { x: 0, y: 2000 },
+            outlines: vec![Outline::Line(vec![SegmentLine {
+                end: Point { x: 500, y: 2000 },
+            }])],
+        };
+        let mut file = FcmFile::from_shapes(
+            vec![square(0, 0, 1000), line],
+            PathTool::TOOL_CUT,
+            &PageSize::A4,
+        );
+        file.piece_table.pieces[0].1.paths[1].tool = PathTool::TOOL_DRAW | PathTool::PATH_OPEN;
+
+        let lengths = file.length_by_tool();
+        assert_eq!(lengths.len(), 2);
+        assert!((lengths[&PathTool::TOOL_CUT] - 40.0).abs() < 1e-9);
+        assert!((lengths[&PathTool::TOOL_DRAW] - 5.0).abs() < 1e-9);
+
+        // Pieces scaled by their transform are measured at their page size
+        let (_, _, _, _, tx, ty) = file.piece_table.pieces[0].1.transform.unwrap();
+        file.piece_table.pieces[0].1.transform = Some((2.0, 0.0, 0.0, 2.0, tx, ty));
+        assert!((file.length_by_tool()[&PathTool::TOOL_CUT] - 80.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_flatten_all() {
+        let shapes = crate::svg_path::tests::unit_parser()
+            .parse("M 0,0 C 0,1000 1000,1000 1000,0 L 500,-500 Z")
+            .unwrap();
+        let mut file = FcmFile::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+        let outlines = |file: &FcmFile| -> Vec<Outline> {
+            file.piece_table.pieces[0].1.paths[0]
+                .shape
+                .as_ref()
+                .unwrap()
+                .outlines
+                .clone()
+        };
+        assert!(outlines(&file)
+            .iter()
+            .any(|it| matches!(it, Outline::Bezier(_))));
+        let end = outlines(&file).last().unwrap().end_point().unwrap();
+
+        file.flatten_all(5);
+
+        let flattened = outlines(&file);
+        assert!(flattened.iter().all(|it| matches!(it, Outline::Line(_))));
+        let flattened_end = flattened.last().unwrap().end_point().unwrap();
+        assert_eq!((flattened_end.x, flattened_end.y), (end.x, end.y));
+        assert!(file.to_bytes().is_ok());
+    }
+
+    #[test]
+    fn test_closed_region_count() {
+        let line = PathShape {
+            start: Point { x: 0, y: 0 },
+            outlines: vec![Outline::Line(vec![SegmentLine {
+                end: Point { x: 3000, y: 0 },
+            }])],
+        };
+        // Donut: outline and hole
+        let file = FcmFile::from_shapes(
+            vec![square(0, 0, 2000), square(500, 500, 1000), line],
+            PathTool::TOOL_CUT,
+            &PageSize::A4,
+        );
+        assert_eq!(file.closed_region_count(), 2);
+    }
+
+    #[test]
+    fn test_fit_warnings() {
+        let page = PageSize::new(100.0, 100.0);
+        let file = FcmFile::from_shapes(vec![square(200, 200, 5000)], PathTool::TOOL_CUT, &page);
+        assert!(file.fit_warnings(&page, 100).is_empty());
+        assert_eq!(
+            file.fit_warnings(&page, 500),
+            vec!["Piece 0 extends into the 5mm margin outside the cuttable area"]
+        );
+
+        let file = FcmFile::from_shapes(vec![square(8000, 0, 5000)], PathTool::TOOL_CUT, &page);
+        assert_eq!(
+            file.fit_warnings(&page, 500),
+            vec!["Piece 0 extends past the page"]
+        );
+    }
+
+    #[test]
+    fn test_flip_y() {
+        let shape = PathShape {
+            start: Point { x: 0, y: 1000 },
+            outlines: vec![Outline::Line(vec![
+                SegmentLine {
+                    end: Point { x: 500, y: 1000 },
+                },
+                SegmentLine {
+                    end: Point { x: 0, y: 1500 },
+                },
+                SegmentLine {
+                    end: Point { x: 0, y: 1000 },
+                },
+            ])],
+        };
+        let mut file = FcmFile::from_shapes(vec![shape], PathTool::TOOL_CUT, &PageSize::A4);
+        file.flip_y(10000);
+
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.y, max.y), (8500, 9000));
+
+        // The corner at y = 1500 ends up at the bottom of the mirrored page
+        let (_, piece) = &file.piece_table.pieces[0];
+        let (_, _, _, _, _, ty) = piece.transform.unwrap();
+        let shape = piece.paths[0].shape.as_ref().unwrap();
+        assert_eq!(shape.start.y as f32 + ty, 9000.0);
+    }
+
+    #[test]
+    fn test_flip_y_marks() {
+        let shapes = vec![square(5000, 5000, 2000)];
+        let mut file =
+            FcmFile::from_shapes_with_mode(shapes, ConvertMode::PrintAndCut, &PageSize::A4);
+        let marks = |file: &FcmFile| -> Vec<(i32, i32)> {
+            let alignment = file.cut_data.alignment.as_ref().unwrap();
+            alignment.marks.iter().map(|p| (p.x, p.y)).collect()
+        };
+        let before = marks(&file);
+        file.flip_y(29700);
+
+        let expected: Vec<(i32, i32)> = before.iter().map(|&(x, y)| (x, 29700 - y)).collect();
+        assert_eq!(marks(&file), expected);
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.y, max.y), (22700, 24700));
+        file.validate().unwrap();
+    }
+
+    #[test]
+    fn test_pieces_mut() {
+        let mut file =
+            FcmFile::from_shapes(vec![square(0, 0, 1000)], PathTool::TOOL_CUT, &PageSize::A4);
+        file.array(1, 2, 2000, 0);
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.x, max.x), (0, 3000));
+
+        for (_, piece) in file.pieces_mut() {
+            for path in &mut piece.paths {
+                path.map_points(|p| Point {
+                    x: p.x * 2,
+                    y: p.y * 2,
+                });
+            }
+        }
+
+        assert_eq!(file.pieces().map(|(id, _)| id).collect::<Vec<_>>(), [0, 1]);
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (-500, -500, 3500, 1500));
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        let shape = PathShape {
+            start: Point { x: 1203, y: 1000 },
+            outlines: vec![Outline::Line(vec![
+                SegmentLine {
+                    end: Point { x: 2451, y: 1000 },
+                },
+                SegmentLine {
+                    end: Point { x: 2420, y: 1010 },
+                },
+                SegmentLine {
+                    end: Point { x: 1203, y: 1000 },
+                },
+            ])],
+        };
+        let mut file = FcmFile::from_shapes(vec![shape], PathTool::TOOL_CUT, &PageSize::A4);
+        let collapsed = file.snap_to_grid(100);
+
+        // 2451 and 2420 both snap to 2400
+        assert_eq!(collapsed, 1);
+        let (_, piece) = &file.piece_table.pieces[0];
+        let (_, _, _, _, tx, ty) = piece.transform.unwrap();
+        assert_eq!((tx, ty), (1800.0, 1000.0));
+        let shape = piece.paths[0].shape.as_ref().unwrap();
+        assert_eq!((shape.start.x, shape.start.y), (-600, 0));
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.x, max.x), (1200, 2400));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let file = FcmFile::from_shapes(
+            vec![square(0, 0, 1000)],
+            PathTool::TOOL_CUT,
+            &PageSize::SQUARE_12,
+        );
+
+        assert_eq!(
+            file.to_json(),
+            concat!(
+                r#"{"cut_width":304.8,"cut_height":304.8,"pieces":[{"id":0,"piece":{"#,
+                r#""width":10,"height":10,"transform":[1,0,0,1,5,5],"label":"","paths":[{"#,
+                r#""tool":2,"shape":{"start":[-5,-5],"outlines":[{"type":"line","points":"#,
+                r#"[[5,-5],[5,5],[-5,5],[-5,-5]]}]},"rhinestone_diameter":null,"#,
+                r#""rhinestones":[]}]}}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let build =
+            || FcmFile::from_shapes(vec![square(0, 0, 500)], PathTool::TOOL_CUT, &PageSize::A4);
+        let a = build();
+        let mut b = build();
+        assert_eq!(a.content_hash().unwrap(), b.content_hash().unwrap());
+
+        b.piece_table.pieces[0].1.label = String::from("ABC");
+        assert_ne!(a.content_hash().unwrap(), b.content_hash().unwrap());
+    }
+}
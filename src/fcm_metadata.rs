@@ -0,0 +1,33 @@
+use nom::combinator::map;
+use nom::number::complete::le_u32;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::cut_data::CutData;
+use crate::file_header::FileHeader;
+use crate::{cut_data, file_header};
+
+/// Header and cut data of a file, without the piece geometry
+#[derive(Debug)]
+pub struct FcmMetadata {
+    pub file_header: FileHeader,
+    pub cut_data: CutData,
+    /// Number of entries in the piece table
+    pub piece_count: u32,
+}
+
+/// Parse up to the count at the start of the piece table, leaving the pieces unread
+pub(crate) fn read_fcm_metadata(input: &[u8]) -> IResult<&[u8], FcmMetadata> {
+    map(
+        tuple((
+            file_header::read_file_header,
+            cut_data::read_cut_data,
+            le_u32,
+        )),
+        |(file_header, cut_data, piece_count)| FcmMetadata {
+            file_header,
+            cut_data,
+            piece_count,
+        },
+    )(input)
+}
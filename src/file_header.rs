@@ -7,27 +7,96 @@ use nom::sequence::tuple;
 use nom::IResult;
 
 use crate::encode::Encode;
+use crate::error::Error;
 use crate::file_variant::FileVariant;
 use crate::generator::Generator;
+use crate::thumbnail::{self, Thumbnail};
 use crate::util::{bool32, read_length_utf16, read_tag, read_utf8_until_null};
 use crate::{file_variant, generator, util};
 
 #[derive(Debug)]
 pub struct FileHeader {
     pub variant: FileVariant,
+    /// Format version as declared by the file, four ASCII characters like "0100"
     pub version: String,
     pub content_id: u32,
+    /// Name shown on the machine, stored as up to 8 bytes of UTF-8 padded with
+    /// zeros. Canvas Workspace writes the project name, the machine the file
+    /// name like "U000001".
     pub short_name: String,
+    /// Stored as up to 255 UTF-16 code units. Canvas Workspace writes a single
+    /// space when there is no name, the machine leaves it empty; both read fine.
     pub long_name: String,
+    /// Same encoding as `long_name`
     pub author_name: String,
+    /// Same encoding as `long_name`
     pub copyright: String,
+    /// Known files use 3×3 blocks for 88×88 and 92×100 thumbnails and 3×4 for
+    /// 78×128 ones, so the thumbnail size can't be derived from the blocks
     pub thumbnail_block_size_width: u8,
     pub thumbnail_block_size_height: u8,
+    /// Monochrome BMP, see [`FileHeader::decode_thumbnail`]
     pub thumbnail: Vec<u8>,
     pub generator: Generator,
     pub print_to_cut: Option<bool>,
 }
 
+impl FileHeader {
+    /// Maximum length of `short_name` in bytes
+    pub const SHORT_NAME_MAX_BYTES: usize = 8;
+    /// Maximum length of `long_name`, `author_name` and `copyright` in UTF-16 code units
+    pub const LONG_NAME_MAX_UNITS: usize = 255;
+    /// What Canvas Workspace writes for a long name or author that isn't set
+    pub const EMPTY_NAME: &'static str = " ";
+
+    /// Decode the thumbnail at the size stored in its BMP header
+    pub fn decode_thumbnail(&self) -> Result<Thumbnail, Error> {
+        thumbnail::decode(&self.thumbnail)
+    }
+
+    /// Set the short name, which has to fit in 8 bytes of UTF-8 and can't
+    /// contain NUL, as that ends the name
+    pub fn set_short_name(&mut self, name: &str) -> Result<(), Error> {
+        if name.len() > Self::SHORT_NAME_MAX_BYTES {
+            return Err(Error {
+                message: format!(
+                    "Invalid name: {:?} is longer than {} bytes",
+                    name,
+                    Self::SHORT_NAME_MAX_BYTES
+                ),
+            });
+        }
+        if name.contains('\0') {
+            return Err(Error {
+                message: format!("Invalid name: {:?} contains NUL", name),
+            });
+        }
+        self.short_name = String::from(name);
+        Ok(())
+    }
+
+    /// Set the long name, which has to fit in 255 UTF-16 code units. An empty
+    /// name is written as a single space, like Canvas Workspace does.
+    pub fn set_long_name(&mut self, name: &str) -> Result<(), Error> {
+        let units = name.encode_utf16().count();
+        if units > Self::LONG_NAME_MAX_UNITS {
+            return Err(Error {
+                message: format!(
+                    "Invalid name: {} UTF-16 code units, at most {} fit",
+                    units,
+                    Self::LONG_NAME_MAX_UNITS
+                ),
+            });
+        }
+        self.long_name = if name.is_empty() {
+            String::from(Self::EMPTY_NAME)
+        } else {
+            String::from(name)
+        };
+        Ok(())
+    }
+}
+
 pub(crate) fn read_file_header(input: &[u8]) -> IResult<&[u8], FileHeader> {
     map(
         tuple((
@@ -86,7 +155,13 @@ pub(crate) fn read_file_header(input: &[u8]) -> IResult<&[u8], FileHeader> {
 impl Encode for FileHeader {
     fn encode(&self, buffer: &mut Vec<u8>) -> std::io::Result<()> {
         self.variant.encode(buffer)?;
-        buffer.write_all(&self.version.as_bytes()[0..4])?;
+        if self.version.len() != 4 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Version must be 4 bytes: {:?}", self.version),
+            ));
+        }
+        buffer.write_all(self.version.as_bytes())?;
         self.content_id.encode(buffer)?;
 
         let mut variable_header: Vec<u8> = vec![];
@@ -114,3 +189,70 @@ impl Encode for FileHeader {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(version: &str) -> FileHeader {
+        FileHeader {
+            variant: FileVariant::FCM,
+            version: String::from(version),
+            content_id: 400000002,
+            short_name: String::new(),
+            long_name: String::from(" "),
+            author_name: String::from(" "),
+            copyright: String::new(),
+            thumbnail_block_size_width: 3,
+            thumbnail_block_size_height: 3,
+            thumbnail: vec![],
+            generator: Generator::App(1),
+            print_to_cut: None,
+        }
+    }
+
+    #[test]
+    fn test_version_round_trip() {
+        for version in ["0100", "0200", "0310"] {
+            let bytes = header(version).encode_to_vec().unwrap();
+            let (rest, parsed) = read_file_header(&bytes).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(parsed.version, version);
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        let mut header = header("0100");
+        header.set_short_name("000042_B").unwrap();
+        header.set_long_name("Blätter 🍂").unwrap();
+        let bytes = header.encode_to_vec().unwrap();
+        let (_, parsed) = read_file_header(&bytes).unwrap();
+        assert_eq!(parsed.short_name, "000042_B");
+        assert_eq!(parsed.long_name, "Blätter 🍂");
+
+        assert!(header.set_short_name("000042_AB").is_err());
+        assert!(header.set_short_name("A\0B").is_err());
+        assert!(header.set_long_name(&"x".repeat(256)).is_err());
+        assert_eq!(header.short_name, "000042_B");
+    }
+
+    #[test]
+    fn test_empty_long_name() {
+        let mut header = header("0100");
+        header.set_long_name("").unwrap();
+        assert_eq!(header.long_name, " ");
+
+        // Files from the machine have empty names, which still round-trip
+        header.long_name = String::new();
+        let bytes = header.encode_to_vec().unwrap();
+        let (_, parsed) = read_file_header(&bytes).unwrap();
+        assert_eq!(parsed.long_name, "");
+    }
+
+    #[test]
+    fn test_invalid_version() {
+        assert!(header("01").encode_to_vec().is_err());
+        assert!(header("01000").encode_to_vec().is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::generator::Generator;
+
+/// Where a file was created.
+///
+/// Both kinds parse into the same structure. The differences found in the
+/// sample files are in the header values only:
+///
+/// | Field                  | Canvas Workspace        | ScanNCut machine            |
+/// |------------------------|-------------------------|-----------------------------|
+/// | `content_id`           | `400000001`/`400000002` | `1009xxxxx`, file number    |
+/// | `short_name`           | project name or empty   | file name, e.g. `U000001`   |
+/// | `long_name`            | project name or `" "`   | empty                       |
+/// | `author_name`          | `" "`                   | empty                       |
+/// | `seam_allowance_width` | `700` or `2000`         | `700`                       |
+/// | piece `label`          | 3 bytes (web) or empty  | empty                       |
+///
+/// Scanned files put every scanned outline in its own piece.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum FileSource {
+    /// Canvas Workspace, web or desktop, or a program like this library that
+    /// writes files the same way
+    CanvasWorkspace,
+    /// Saved on the cutting machine itself, e.g. from a scan
+    Machine,
+}
+
+impl FileSource {
+    /// Source of a file written by `generator`
+    pub fn from_generator(generator: Generator) -> FileSource {
+        match generator {
+            Generator::App(_) | Generator::Web(_) => FileSource::CanvasWorkspace,
+            Generator::Device(_, _) => FileSource::Machine,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_generator() {
+        assert_eq!(
+            FileSource::from_generator(Generator::Web(100)),
+            FileSource::CanvasWorkspace
+        );
+        assert_eq!(
+            FileSource::from_generator(Generator::App(206)),
+            FileSource::CanvasWorkspace
+        );
+        assert_eq!(
+            FileSource::from_generator(Generator::Device(Generator::SCANNCUT_DEVICE_ID, 1819)),
+            FileSource::Machine
+        );
+    }
+}
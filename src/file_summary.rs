@@ -0,0 +1,57 @@
+use std::fs;
+
+use crate::{Error, FcmFile, FileType, FileVariant};
+
+/// Overview of one file, read from its header and cut data only
+#[derive(Debug, Clone)]
+pub struct FileSummary {
+    /// File name without the directory
+    pub name: String,
+    pub variant: FileVariant,
+    pub file_type: FileType,
+    /// Whether registration mark positions are present, see
+    /// [`CutData::is_print_and_cut`](crate::CutData::is_print_and_cut)
+    pub is_print_and_cut: bool,
+    pub piece_count: u32,
+    /// Number of registration marks
+    pub marks: usize,
+}
+
+/// Summarize every `.fcm` file directly inside `dir`, sorted by name. Files
+/// that can't be read or parsed are skipped, the piece geometry is not parsed.
+pub fn scan_directory<T: AsRef<std::path::Path>>(dir: T) -> Result<Vec<FileSummary>, Error> {
+    let entries = fs::read_dir(dir.as_ref()).map_err(|e| Error {
+        message: format!("Could not read directory: {0}", e),
+    })?;
+
+    let mut summaries = vec![];
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("fcm"))
+        {
+            continue;
+        }
+        let Ok(data) = fs::read(&path) else {
+            continue;
+        };
+        let Ok(metadata) = FcmFile::read_metadata(&data) else {
+            continue;
+        };
+        summaries.push(FileSummary {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            variant: metadata.file_header.variant,
+            file_type: metadata.cut_data.file_type,
+            is_print_and_cut: metadata.cut_data.is_print_and_cut(),
+            piece_count: metadata.piece_count,
+            marks: metadata
+                .cut_data
+                .alignment
+                .as_ref()
+                .map_or(0, |alignment| alignment.marks.len()),
+        });
+    }
+    summaries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(summaries)
+}
@@ -6,26 +6,67 @@ use nom::IResult;
 
 use crate::encode::Encode;
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum FileType {
+    /// Cut and/or draw without scanning. Whether a path is cut or drawn is
+    /// decided by its tool, so draw-only (plotter) files use this type too.
     Cut,
+    /// Printed artwork with registration marks scanned before cutting
     PrintAndCut,
+    Unknown(u32),
+}
+
+impl FileType {
+    /// Map the on-disk file type value to its variant
+    pub fn from_raw(raw: u32) -> FileType {
+        match raw {
+            0x10 => FileType::Cut,
+            0x38 => FileType::PrintAndCut,
+            _ => FileType::Unknown(raw),
+        }
+    }
+
+    /// The value written to disk for this file type
+    pub fn to_raw(&self) -> u32 {
+        match self {
+            FileType::Cut => 0x10,
+            FileType::PrintAndCut => 0x38,
+            FileType::Unknown(raw) => *raw,
+        }
+    }
 }
 
 pub(crate) fn read_file_type(input: &[u8]) -> IResult<&[u8], FileType> {
-    map_res(le_u32, |data| match data {
-        0x10 => Ok(FileType::Cut),
-        0x38 => Ok(FileType::PrintAndCut),
-        _ => Err(format!("Unable to parse file type: {data}")),
+    map_res(le_u32, |data| match FileType::from_raw(data) {
+        FileType::Unknown(_) => Err(format!("Unable to parse file type: {data}")),
+        file_type => Ok(file_type),
     })(input)
 }
 
 impl Encode for FileType {
     fn encode(&self, buffer: &mut Vec<u8>) -> std::io::Result<()> {
-        match self {
-            FileType::Cut => buffer.write_all(&0x10u32.to_le_bytes())?,
-            FileType::PrintAndCut => buffer.write_all(&0x38u32.to_le_bytes())?,
-        };
+        buffer.write_all(&self.to_raw().to_le_bytes())?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_round_trip() {
+        for file_type in [FileType::Cut, FileType::PrintAndCut] {
+            assert_eq!(FileType::from_raw(file_type.to_raw()), file_type);
+        }
+        assert_eq!(FileType::Cut.to_raw(), 0x10);
+        assert_eq!(FileType::PrintAndCut.to_raw(), 0x38);
+    }
+
+    #[test]
+    fn test_unknown_round_trip() {
+        assert_eq!(FileType::from_raw(0x20), FileType::Unknown(0x20));
+        assert_eq!(FileType::Unknown(0x20).to_raw(), 0x20);
+        assert!(read_file_type(&0x20u32.to_le_bytes()).is_err());
+    }
+}
@@ -1,31 +1,99 @@
 use std::io::Write;
 
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::combinator::map;
+use nom::bytes::complete::take;
+use nom::combinator::map_opt;
 use nom::IResult;
 
 use crate::encode::Encode;
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum FileVariant {
     FCM,
     VCM,
+    Unknown([u8; 4]),
+}
+
+impl FileVariant {
+    /// Map the 4-byte signature at the start of a file to its variant
+    pub fn from_raw(raw: [u8; 4]) -> FileVariant {
+        match &raw {
+            b"#FCM" => FileVariant::FCM,
+            b"#VCM" => FileVariant::VCM,
+            _ => FileVariant::Unknown(raw),
+        }
+    }
+
+    /// The 4-byte signature written at the start of a file
+    pub fn to_raw(&self) -> [u8; 4] {
+        match self {
+            FileVariant::FCM => *b"#FCM",
+            FileVariant::VCM => *b"#VCM",
+            FileVariant::Unknown(raw) => *raw,
+        }
+    }
+}
+
+/// Check whether `bytes` start with an FCM/VCM signature, followed by a
+/// four-digit version, without parsing the rest of the file
+pub fn is_fcm(bytes: &[u8]) -> bool {
+    peek_variant(bytes).is_some()
+        && bytes.len() >= 8
+        && bytes[4..8].iter().all(|it| it.is_ascii_digit())
+}
+
+/// Read the variant from the signature at the start of `bytes`
+pub fn peek_variant(bytes: &[u8]) -> Option<FileVariant> {
+    read_variant(bytes).ok().map(|(_, variant)| variant)
 }
 
 pub(crate) fn read_variant(input: &[u8]) -> IResult<&[u8], FileVariant> {
-    alt((
-        map(tag("#FCM"), |_| FileVariant::FCM),
-        map(tag("#VCM"), |_| FileVariant::VCM),
-    ))(input)
+    map_opt(take(4usize), |data: &[u8]| {
+        match FileVariant::from_raw([data[0], data[1], data[2], data[3]]) {
+            FileVariant::Unknown(_) => None,
+            variant => Some(variant),
+        }
+    })(input)
 }
 
 impl Encode for FileVariant {
     fn encode(&self, buffer: &mut Vec<u8>) -> std::io::Result<()> {
-        match self {
-            FileVariant::FCM => buffer.write_all("#FCM".as_bytes())?,
-            FileVariant::VCM => buffer.write_all("#VCM".as_bytes())?,
-        };
+        buffer.write_all(&self.to_raw())?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peek_signature() {
+        let header = b"#VCM0100\x01\x00";
+        assert!(is_fcm(header));
+        assert_eq!(peek_variant(header), Some(FileVariant::VCM));
+        assert_eq!(peek_variant(b"#FCM"), Some(FileVariant::FCM));
+
+        let random = [0x8f, 0x12, 0xc4, 0x07, 0x33, 0xe0, 0x51, 0x9a];
+        assert!(!is_fcm(&random));
+        assert_eq!(peek_variant(&random), None);
+        assert!(!is_fcm(b"#FC"));
+        assert!(!is_fcm(b"#FCMabcd"));
+    }
+
+    #[test]
+    fn test_raw_round_trip() {
+        for variant in [FileVariant::FCM, FileVariant::VCM] {
+            assert_eq!(FileVariant::from_raw(variant.to_raw()), variant);
+        }
+        assert_eq!(FileVariant::FCM.to_raw(), *b"#FCM");
+        assert_eq!(FileVariant::VCM.to_raw(), *b"#VCM");
+    }
+
+    #[test]
+    fn test_unknown_round_trip() {
+        let variant = FileVariant::from_raw(*b"#XCM");
+        assert_eq!(variant, FileVariant::Unknown(*b"#XCM"));
+        assert_eq!(variant.to_raw(), *b"#XCM");
+        assert!(read_variant(b"#XCM").is_err());
+    }
+}
@@ -1,63 +1,136 @@
 use std::io::Write;
 
-use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::map;
-use nom::number::complete::le_u32;
-use nom::sequence::tuple;
+use nom::number::complete::le_u64;
 use nom::IResult;
 
 use crate::encode::Encode;
 
-#[derive(Debug)]
+/// The program that wrote a file.
+///
+/// Known values from the sample files:
+///
+/// | Generator              | Written by                         |
+/// |------------------------|------------------------------------|
+/// | `Web(100)`, `Web(200)` | Canvas Workspace for the web       |
+/// | `App(206)`             | Canvas Workspace desktop app       |
+/// | `Device(4352, 1819)`   | ScanNCut machine, for scanned data |
+///
+/// Files created by this library use `App(1)`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Generator {
+    /// Canvas Workspace desktop app, with its version
     App(u32),
+    /// Canvas Workspace for the web, with its version
     Web(u32),
+    /// Cutting machine with a device id and firmware version. Anything that
+    /// isn't tagged as app or web is read as a device.
     Device(u32, u32),
 }
 
-pub(crate) fn read_generator(input: &[u8]) -> IResult<&[u8], Generator> {
-    alt((
-        read_generator_app,
-        read_generator_web,
-        read_generator_device,
-    ))(input)
-}
+impl Generator {
+    /// Device id of the ScanNCut machines in the sample files
+    pub const SCANNCUT_DEVICE_ID: u32 = 4352;
+
+    /// Generator for files written by Canvas Workspace for the web
+    pub fn scanncut_canvas() -> Generator {
+        Generator::Web(200)
+    }
 
-fn read_generator_app(input: &[u8]) -> IResult<&[u8], Generator> {
-    map(tuple((tag("1APP"), le_u32)), |(_, version)| {
+    /// Generator for files written by a desktop app with the given version,
+    /// like this library's `App(1)`
+    pub fn user_app(version: u32) -> Generator {
         Generator::App(version)
-    })(input)
-}
+    }
+
+    /// Human readable name of the program that wrote the file
+    pub fn description(&self) -> String {
+        match self {
+            Generator::App(1) => String::from("fcmlib"),
+            Generator::App(version) => format!("Canvas Workspace app (version {})", version),
+            Generator::Web(version) => format!("Canvas Workspace web (version {})", version),
+            Generator::Device(Generator::SCANNCUT_DEVICE_ID, version) => {
+                format!("ScanNCut machine (firmware {})", version)
+            }
+            Generator::Device(id, version) => {
+                format!("Unknown device {} (version {})", id, version)
+            }
+        }
+    }
+
+    /// Map the 8 bytes stored in the header to a generator
+    pub fn from_raw(raw: [u8; 8]) -> Generator {
+        let version = u32::from_le_bytes([raw[4], raw[5], raw[6], raw[7]]);
+        match &raw[0..4] {
+            b"1APP" => Generator::App(version),
+            b"1WEB" => Generator::Web(version),
+            id => Generator::Device(u32::from_le_bytes([id[0], id[1], id[2], id[3]]), version),
+        }
+    }
 
-fn read_generator_web(input: &[u8]) -> IResult<&[u8], Generator> {
-    map(tuple((tag("1WEB"), le_u32)), |(_, version)| {
-        Generator::Web(version)
-    })(input)
+    /// The 8 bytes stored in the header for this generator
+    pub fn to_raw(&self) -> [u8; 8] {
+        let (tag, version) = match self {
+            Generator::App(version) => (*b"1APP", *version),
+            Generator::Web(version) => (*b"1WEB", *version),
+            Generator::Device(id, version) => (id.to_le_bytes(), *version),
+        };
+        let mut raw = [0u8; 8];
+        raw[0..4].copy_from_slice(&tag);
+        raw[4..8].copy_from_slice(&version.to_le_bytes());
+        raw
+    }
 }
 
-fn read_generator_device(input: &[u8]) -> IResult<&[u8], Generator> {
-    map(tuple((le_u32, le_u32)), |(device, version)| {
-        Generator::Device(device, version)
-    })(input)
+pub(crate) fn read_generator(input: &[u8]) -> IResult<&[u8], Generator> {
+    map(le_u64, |raw| Generator::from_raw(raw.to_le_bytes()))(input)
 }
 
 impl Encode for Generator {
     fn encode(&self, buffer: &mut Vec<u8>) -> std::io::Result<()> {
-        match self {
-            Generator::App(version) => {
-                buffer.write_all("1APP".as_bytes())?;
-                version.encode(buffer)?;
-            }
-            Generator::Web(version) => {
-                buffer.write_all("1WEB".as_bytes())?;
-                version.encode(buffer)?;
-            }
-            Generator::Device(id, version) => {
-                id.encode(buffer)?;
-                version.encode(buffer)?;
-            }
-        }
+        buffer.write_all(&self.to_raw())?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptions() {
+        assert_eq!(Generator::App(1).description(), "fcmlib");
+        assert_eq!(
+            Generator::App(206).description(),
+            "Canvas Workspace app (version 206)"
+        );
+        assert_eq!(
+            Generator::scanncut_canvas().description(),
+            "Canvas Workspace web (version 200)"
+        );
+        assert_eq!(
+            Generator::Device(4352, 1819).description(),
+            "ScanNCut machine (firmware 1819)"
+        );
+        assert_eq!(
+            Generator::Device(7, 1).description(),
+            "Unknown device 7 (version 1)"
+        );
+    }
+
+    #[test]
+    fn test_raw_round_trip() {
+        for generator in [
+            Generator::user_app(206),
+            Generator::Web(100),
+            Generator::Device(4352, 1819),
+        ] {
+            let raw = generator.to_raw();
+            assert_eq!(Generator::from_raw(raw), generator);
+            let (rest, parsed) = read_generator(&raw).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(parsed, generator);
+        }
+        assert_eq!(&Generator::App(1).to_raw(), b"1APP\x01\x00\x00\x00");
+    }
+}
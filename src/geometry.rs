@@ -0,0 +1,223 @@
+use crate::{Outline, PathShape, Point, SegmentBezier, SegmentLine};
+
+/// Default flattening tolerance in FCM units (0.05mm)
+pub(crate) const DEFAULT_TOLERANCE: i32 = 5;
+
+/// Flatten a cubic bezier into a polyline, excluding `from` and including the end point.
+///
+/// The segment count is chosen from the second differences of the control polygon so the
+/// polyline deviates from the curve by at most `tolerance` units.
+pub(crate) fn flatten_cubic(from: Point, segment: &SegmentBezier, tolerance: i32) -> Vec<Point> {
+    let tolerance = tolerance.max(1) as f64;
+    let (p0, p1, p2, p3) = (
+        to_f64(from),
+        to_f64(segment.control1),
+        to_f64(segment.control2),
+        to_f64(segment.end),
+    );
+
+    let dd1 = length(p0.0 - 2.0 * p1.0 + p2.0, p0.1 - 2.0 * p1.1 + p2.1);
+    let dd2 = length(p1.0 - 2.0 * p2.0 + p3.0, p1.1 - 2.0 * p2.1 + p3.1);
+    let steps = ((6.0 * dd1.max(dd2)) / (8.0 * tolerance))
+        .sqrt()
+        .ceil()
+        .max(1.0) as usize;
+
+    (1..=steps)
+        .map(|i| {
+            if i == steps {
+                segment.end
+            } else {
+                let (x, y) = cubic_point(p0, p1, p2, p3, i as f64 / steps as f64);
+                from_f64(x, y)
+            }
+        })
+        .collect()
+}
+
+/// Evaluate a cubic bezier at parameter `t`
+pub(crate) fn cubic_point(
+    p0: (f64, f64),
+    p1: (f64, f64),
+    p2: (f64, f64),
+    p3: (f64, f64),
+    t: f64,
+) -> (f64, f64) {
+    let mt = 1.0 - t;
+    let a = mt * mt * mt;
+    let b = 3.0 * mt * mt * t;
+    let c = 3.0 * mt * t * t;
+    let d = t * t * t;
+    (
+        a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+        a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+    )
+}
+
+pub(crate) fn to_f64(point: Point) -> (f64, f64) {
+    (point.x as f64, point.y as f64)
+}
+
+pub(crate) fn from_f64(x: f64, y: f64) -> Point {
+    Point {
+        x: x.round() as i32,
+        y: y.round() as i32,
+    }
+}
+
+pub(crate) fn length(dx: f64, dy: f64) -> f64 {
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Round a coordinate to the nearest multiple of `grid`, halves rounding up
+pub(crate) fn snap(value: i32, grid: i32) -> i32 {
+    if grid <= 1 {
+        return value;
+    }
+    (value + grid / 2).div_euclid(grid) * grid
+}
+
+pub(crate) fn snap_point(point: Point, grid: i32) -> Point {
+    Point {
+        x: snap(point.x, grid),
+        y: snap(point.y, grid),
+    }
+}
+
+/// Convex hull of `points` in counter-clockwise order (in a y-up frame),
+/// without collinear points, using Andrew's monotone chain
+pub(crate) fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut points: Vec<Point> = points.to_vec();
+    points.sort_by_key(|p| (p.x, p.y));
+    points.dedup_by(|a, b| a.x == b.x && a.y == b.y);
+    if points.len() < 3 {
+        return points;
+    }
+
+    let turn = |o: Point, a: Point, b: Point| {
+        (a.x - o.x) as i64 * (b.y - o.y) as i64 - (a.y - o.y) as i64 * (b.x - o.x) as i64
+    };
+    let mut hull: Vec<Point> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let base = hull.len();
+        for point in pass {
+            while hull.len() >= base + 2
+                && turn(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point is the first of the other pass
+        hull.pop();
+    }
+    hull
+}
+
+/// Build a closed Line shape through `points`, adding the closing segment back to the first point
+pub(crate) fn polygon_to_shape(points: &[Point]) -> PathShape {
+    let start = points[0];
+    let mut segments: Vec<SegmentLine> =
+        points[1..].iter().map(|&end| SegmentLine { end }).collect();
+    if segments.last().map(|it| (it.end.x, it.end.y)) != Some((start.x, start.y)) {
+        segments.push(SegmentLine { end: start });
+    }
+
+    PathShape {
+        start,
+        outlines: vec![Outline::Line(segments)],
+    }
+}
+
+/// Whether two parts of the polyline through `points` come closer than `min`
+/// while being more than `2 * min` apart along the polyline, like the sides of
+/// a thin slot or spike. The polyline is sampled every `min / 4` so long
+/// segments are compared along their length, not just at their ends.
+pub(crate) fn has_narrow_gap(points: &[(f64, f64)], closed: bool, min: f64) -> bool {
+    let step = min / 4.0;
+    // x, y and distance along the polyline
+    let mut samples: Vec<(f64, f64, f64)> = vec![];
+    let mut total = 0.0;
+    for w in points.windows(2) {
+        let (dx, dy) = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+        let segment = length(dx, dy);
+        let steps = (segment / step).ceil().max(1.0) as usize;
+        for i in 0..steps {
+            let t = i as f64 / steps as f64;
+            samples.push((w[0].0 + t * dx, w[0].1 + t * dy, total + t * segment));
+        }
+        total += segment;
+    }
+    // A closed polyline ends on its first point
+    if let (false, Some(&(x, y))) = (closed, points.last()) {
+        samples.push((x, y, total));
+    }
+
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for (i, a) in samples.iter().enumerate() {
+        for b in &samples[i + 1..] {
+            if b.0 - a.0 >= min {
+                break;
+            }
+            if length(b.0 - a.0, b.1 - a.1) >= min {
+                continue;
+            }
+            let along = (b.2 - a.2).abs();
+            let along = if closed {
+                along.min(total - along)
+            } else {
+                along
+            };
+            if along > 2.0 * min {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Shortest distance between the segments `a0`-`a1` and `b0`-`b1`, 0 if they
+/// cross or touch
+pub(crate) fn segment_distance(
+    a0: (f64, f64),
+    a1: (f64, f64),
+    b0: (f64, f64),
+    b1: (f64, f64),
+) -> f64 {
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let (d1, d2) = (cross(a0, a1, b0), cross(a0, a1, b1));
+    let (d3, d4) = (cross(b0, b1, a0), cross(b0, b1, a1));
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return 0.0;
+    }
+    point_segment_distance(a0, b0, b1)
+        .min(point_segment_distance(a1, b0, b1))
+        .min(point_segment_distance(b0, a0, a1))
+        .min(point_segment_distance(b1, a0, a1))
+}
+
+/// Shortest distance from `p` to the segment `a`-`b`
+pub(crate) fn point_segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    length(p.0 - (a.0 + t * dx), p.1 - (a.1 + t * dy))
+}
+
+/// Even-odd test of `p` against the closed polygon through `points`
+pub(crate) fn point_in_polygon(p: (f64, f64), points: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    for (i, &(ax, ay)) in points.iter().enumerate() {
+        let (bx, by) = points[(i + 1) % points.len()];
+        if (ay > p.1) != (by > p.1) && p.0 < ax + (p.1 - ay) * (bx - ax) / (by - ay) {
+            inside = !inside;
+        }
+    }
+    inside
+}
@@ -0,0 +1,160 @@
+//! Import of cut files from plain coordinate lists
+//!
+//! Polygons are given like GeoJSON polygons: a list of rings, the first the
+//! outline and the rest holes in it, each ring a list of points. Rings may
+//! repeat their first point at the end. Coordinates are already projected
+//! onto the page, with y pointing down; map data in latitude and longitude
+//! has to be projected and flipped first.
+//!
+//! # Example
+//! ```
+//! use fcmlib::import::{self, Units};
+//! use fcmlib::registration_marks::PageSize;
+//!
+//! let square = vec![(10.0, 10.0), (60.0, 10.0), (60.0, 60.0), (10.0, 60.0)];
+//! let file = import::from_polygons(vec![vec![square]], Units::Millimeters, &PageSize::A4);
+//! assert_eq!(file.pieces().count(), 1);
+//! ```
+
+use crate::registration_marks::PageSize;
+use crate::{FcmFile, Path, PathShape, PathTool, Piece, Point};
+
+/// Unit of imported coordinates
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Units {
+    Millimeters,
+    Inches,
+    /// FCM units, hundredths of a millimeter
+    Fcm,
+}
+
+impl Units {
+    /// Convert a coordinate to whole FCM units
+    pub fn to_fcm(&self, value: f64) -> i32 {
+        let units = match self {
+            Units::Millimeters => value * 100.0,
+            Units::Inches => value * 2540.0,
+            Units::Fcm => value,
+        };
+        units.round() as i32
+    }
+}
+
+/// Build a cut file with one piece per polygon, in order, on `page`. Each
+/// ring becomes a closed cut path, the outline winding counter-clockwise and
+/// the holes clockwise like [`boolean`](crate::boolean) results. Rings with
+/// fewer than three points are skipped, and so are polygons whose outline is
+/// skipped. Like [`FcmFile::from_pieces`], pieces past the last 16-bit id are
+/// left out.
+pub fn from_polygons(
+    polygons: Vec<Vec<Vec<(f64, f64)>>>,
+    units: Units,
+    page: &PageSize,
+) -> FcmFile {
+    let mut pieces = vec![];
+    for rings in polygons {
+        let mut paths = vec![];
+        for (index, ring) in rings.iter().enumerate() {
+            if ring.len() < 3 {
+                if index == 0 {
+                    break;
+                }
+                continue;
+            }
+            let points: Vec<Point> = ring
+                .iter()
+                .map(|&(x, y)| Point {
+                    x: units.to_fcm(x),
+                    y: units.to_fcm(y),
+                })
+                .collect();
+            let mut shape = PathShape::from_fcm_points(&points, true);
+            let is_hole = index > 0;
+            if is_hole == (shape.signed_area() > 0.0) {
+                shape = shape.reversed();
+            }
+            paths.push(Path {
+                tool: PathTool::TOOL_CUT,
+                shape: Some(shape),
+                rhinestone_diameter: None,
+                rhinestones: vec![],
+            });
+        }
+        if !paths.is_empty() {
+            pieces.push(Piece::from_paths(paths));
+        }
+    }
+
+    FcmFile::from_pieces(pieces, page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_holed_polygon() {
+        // A 50mm square with a 20mm square hole, both rings closed and
+        // wound the same way
+        let outer = vec![
+            (10.0, 10.0),
+            (60.0, 10.0),
+            (60.0, 60.0),
+            (10.0, 60.0),
+            (10.0, 10.0),
+        ];
+        let hole = vec![(25.0, 25.0), (45.0, 25.0), (45.0, 45.0), (25.0, 45.0)];
+        let triangle = vec![(100.0, 10.0), (120.0, 10.0), (110.0, 30.0)];
+        let polygons = vec![vec![outer, hole], vec![triangle], vec![vec![(0.0, 0.0)]]];
+        let file = from_polygons(polygons, Units::Millimeters, &PageSize::A4);
+        file.validate().unwrap();
+
+        let pieces: Vec<_> = file.pieces().collect();
+        assert_eq!(pieces.len(), 2);
+        assert_eq!((pieces[0].0, pieces[1].0), (0, 1));
+        let (_, piece) = pieces[0];
+        assert_eq!(piece.paths.len(), 2);
+        assert_eq!((piece.width, piece.height), (5000, 5000));
+        let (min, max) = file.bounds().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (1000, 1000, 12000, 6000));
+
+        let areas: Vec<f64> = piece
+            .paths
+            .iter()
+            .map(|path| path.shape.as_ref().unwrap().signed_area())
+            .collect();
+        assert_eq!(areas, vec![5000.0 * 5000.0, -2000.0 * 2000.0]);
+        assert_eq!(file.used_area_mm2(), 2500.0 - 400.0 + 200.0);
+
+        let parsed = FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        assert_eq!(parsed.pieces().count(), 2);
+
+        // The thumbnail shows both pieces where they are on the page
+        let shapes = file.pieces().flat_map(|(_, piece)| {
+            piece.paths.iter().map(|path| {
+                let mut path = path.clone();
+                path.map_points(|p| piece.to_page_point(p));
+                path.shape.unwrap()
+            })
+        });
+        let single = FcmFile::from_shapes(shapes.collect(), PathTool::TOOL_CUT, &PageSize::A4);
+        assert_eq!(file.file_header.thumbnail, single.file_header.thumbnail);
+    }
+
+    #[test]
+    fn test_piece_id_limit() {
+        let triangle = vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+        let polygons = vec![vec![triangle]; u16::MAX as usize + 2];
+        let file = from_polygons(polygons, Units::Fcm, &PageSize::A4);
+        let ids: Vec<u16> = file.pieces().map(|(id, _)| id).collect();
+        assert_eq!(ids.len(), u16::MAX as usize + 1);
+        assert_eq!(ids.last(), Some(&u16::MAX));
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(Units::Millimeters.to_fcm(1.5), 150);
+        assert_eq!(Units::Inches.to_fcm(0.5), 1270);
+        assert_eq!(Units::Fcm.to_fcm(12.4), 12);
+    }
+}
@@ -0,0 +1,125 @@
+//! Jobs spanning several mats
+//!
+//! # Example
+//! ```
+//! use fcmlib::job::Job;
+//! use fcmlib::registration_marks::PageSize;
+//! use fcmlib::svg_path::{SvgConfig, SvgPathParser};
+//! use fcmlib::PathTool;
+//!
+//! let parser = SvgPathParser::new(SvgConfig::default());
+//! let shapes = parser.parse("M 0,0 L 100,0 L 100,100 Z").unwrap();
+//! let job = Job::from_shapes(shapes, PathTool::TOOL_CUT, &PageSize::A4);
+//! assert_eq!(job.pages.len(), 1);
+//! ```
+
+use crate::registration_marks::PageSize;
+use crate::{FcmFile, PathShape, PathTool, Point};
+
+/// Distance kept from the page edges when packing, in FCM units
+pub const MARGIN_FCM: i32 = 1000;
+
+/// Distance kept between packed shapes, in FCM units
+pub const SPACING_FCM: i32 = 500;
+
+/// A set of files cut one after another, one per mat
+#[derive(Debug)]
+pub struct Job {
+    pub pages: Vec<FcmFile>,
+}
+
+impl Job {
+    /// Pack the shapes onto as many pages as needed, in rows from the top
+    /// left of each page and in the given order. Where the shapes were on the
+    /// input page is ignored. Each page is a file like
+    /// [`FcmFile::from_shapes`] creates. A shape too large for the page gets a
+    /// page of its own, which [`FcmFile::fit_warnings`] reports.
+    pub fn from_shapes(shapes: Vec<PathShape>, tool: PathTool, page: &PageSize) -> Job {
+        let (area_min, area_max) = page.cuttable_area(MARGIN_FCM);
+        let mut pages: Vec<Vec<PathShape>> = vec![];
+        let mut current: Vec<PathShape> = vec![];
+        let mut cursor = area_min;
+        let mut row_height = 0;
+
+        for mut shape in shapes {
+            let (min, max) = shape.bounds();
+            let (width, height) = (max.x - min.x, max.y - min.y);
+
+            if !current.is_empty() && cursor.x + width > area_max.x {
+                cursor = Point {
+                    x: area_min.x,
+                    y: cursor.y + row_height + SPACING_FCM,
+                };
+                row_height = 0;
+            }
+            if !current.is_empty() && cursor.y + height > area_max.y {
+                pages.push(std::mem::take(&mut current));
+                cursor = area_min;
+                row_height = 0;
+            }
+
+            shape.translate(cursor.x - min.x, cursor.y - min.y);
+            current.push(shape);
+            cursor.x += width + SPACING_FCM;
+            row_height = row_height.max(height);
+        }
+        if !current.is_empty() {
+            pages.push(current);
+        }
+
+        Job {
+            pages: pages
+                .into_iter()
+                .map(|shapes| FcmFile::from_shapes(shapes, tool, page))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Outline, SegmentLine};
+
+    fn square(size: i32) -> PathShape {
+        let corner = |x, y| SegmentLine {
+            end: Point { x, y },
+        };
+        PathShape {
+            start: Point { x: 0, y: 0 },
+            outlines: vec![Outline::Line(vec![
+                corner(size, 0),
+                corner(size, size),
+                corner(0, size),
+                corner(0, 0),
+            ])],
+        }
+    }
+
+    #[test]
+    fn test_from_shapes_two_pages() {
+        // A 100mm square mat fits one 60mm square per page
+        let page = PageSize::new(100.0, 100.0);
+        let job = Job::from_shapes(vec![square(6000), square(6000)], PathTool::TOOL_CUT, &page);
+        assert_eq!(job.pages.len(), 2);
+        for file in &job.pages {
+            let (min, max) = file.bounds().unwrap();
+            assert_eq!((min.x, min.y, max.x, max.y), (1000, 1000, 7000, 7000));
+            assert!(file.fit_warnings(&page, MARGIN_FCM).is_empty());
+            file.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_from_shapes_rows() {
+        let page = PageSize::new(100.0, 100.0);
+        let shapes = vec![square(3000), square(3000), square(3000), square(3000)];
+        let job = Job::from_shapes(shapes, PathTool::TOOL_CUT, &page);
+        assert_eq!(job.pages.len(), 1);
+        let (min, max) = job.pages[0].bounds().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (1000, 1000, 7500, 7500));
+
+        let job = Job::from_shapes(vec![], PathTool::TOOL_CUT, &page);
+        assert!(job.pages.is_empty());
+    }
+}
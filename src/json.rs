@@ -0,0 +1,152 @@
+use std::fmt::Write;
+
+use crate::{FcmFile, Outline, Path, PathShape, Piece, Point};
+
+/// Hand-written JSON output of the geometry, with coordinates in mm
+pub(crate) trait ToJson {
+    fn write_json(&self, out: &mut String);
+
+    fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+}
+
+fn write_list<T, F: FnMut(&T, &mut String)>(items: &[T], out: &mut String, mut write_item: F) {
+    out.push('[');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_item(item, out);
+    }
+    out.push(']');
+}
+
+fn write_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// FCM units are hundredths of a millimeter
+fn write_mm(value: i64, out: &mut String) {
+    let _ = write!(out, "{}", value as f64 / 100.0);
+}
+
+impl ToJson for Point {
+    fn write_json(&self, out: &mut String) {
+        out.push('[');
+        write_mm(self.x as i64, out);
+        out.push(',');
+        write_mm(self.y as i64, out);
+        out.push(']');
+    }
+}
+
+impl ToJson for Outline {
+    fn write_json(&self, out: &mut String) {
+        match self {
+            Outline::Line(segments) => {
+                out.push_str("{\"type\":\"line\",\"points\":");
+                write_list(segments, out, |segment, out| segment.end.write_json(out));
+            }
+            Outline::Bezier(segments) => {
+                out.push_str("{\"type\":\"bezier\",\"segments\":");
+                write_list(segments, out, |segment, out| {
+                    write_list(
+                        &[segment.control1, segment.control2, segment.end],
+                        out,
+                        |point, out| point.write_json(out),
+                    )
+                });
+            }
+        }
+        out.push('}');
+    }
+}
+
+impl ToJson for PathShape {
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"start\":");
+        self.start.write_json(out);
+        out.push_str(",\"outlines\":");
+        write_list(&self.outlines, out, |outline, out| outline.write_json(out));
+        out.push('}');
+    }
+}
+
+impl ToJson for Path {
+    fn write_json(&self, out: &mut String) {
+        let _ = write!(out, "{{\"tool\":{},\"shape\":", self.tool.bits());
+        match &self.shape {
+            Some(shape) => shape.write_json(out),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"rhinestone_diameter\":");
+        match self.rhinestone_diameter {
+            Some(diameter) => write_mm(diameter as i64, out),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"rhinestones\":");
+        write_list(&self.rhinestones, out, |point, out| point.write_json(out));
+        out.push('}');
+    }
+}
+
+impl ToJson for Piece {
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"width\":");
+        write_mm(self.width as i64, out);
+        out.push_str(",\"height\":");
+        write_mm(self.height as i64, out);
+        out.push_str(",\"transform\":");
+        match self.transform {
+            // The translation is in FCM units like the paths, the matrix part is unitless
+            Some((a, b, c, d, tx, ty)) => {
+                let _ = write!(
+                    out,
+                    "[{},{},{},{},{},{}]",
+                    a,
+                    b,
+                    c,
+                    d,
+                    tx as f64 / 100.0,
+                    ty as f64 / 100.0
+                );
+            }
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"label\":");
+        write_string(&self.label, out);
+        out.push_str(",\"paths\":");
+        write_list(&self.paths, out, |path, out| path.write_json(out));
+        out.push('}');
+    }
+}
+
+impl ToJson for FcmFile {
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"cut_width\":");
+        write_mm(self.cut_data.cut_width as i64, out);
+        out.push_str(",\"cut_height\":");
+        write_mm(self.cut_data.cut_height as i64, out);
+        out.push_str(",\"pieces\":");
+        write_list(&self.piece_table.pieces, out, |(id, piece), out| {
+            let _ = write!(out, "{{\"id\":{},\"piece\":", id);
+            piece.write_json(out);
+            out.push('}');
+        });
+        out.push('}');
+    }
+}
@@ -1,45 +1,68 @@
 extern crate core;
 
 pub use crate::alignment_data::AlignmentData;
+pub use crate::convert_mode::ConvertMode;
 pub use crate::cut_data::CutData;
 pub use crate::error::Error;
 pub use crate::fcm_file::FcmFile;
+pub use crate::fcm_metadata::FcmMetadata;
 pub use crate::file_header::FileHeader;
+pub use crate::file_source::FileSource;
+pub use crate::file_summary::{scan_directory, FileSummary};
 pub use crate::file_type::FileType;
-pub use crate::file_variant::FileVariant;
+pub use crate::file_variant::{is_fcm, peek_variant, FileVariant};
 pub use crate::generator::Generator;
+pub use crate::mark_scaling::MarkScaling;
 pub use crate::outline::Outline;
 pub use crate::path::Path;
 pub use crate::path_shape::PathShape;
 pub use crate::path_tool::PathTool;
 pub use crate::piece::Piece;
+pub use crate::piece_builder::PieceBuilder;
 pub use crate::piece_restrictions::PieceRestrictions;
 pub use crate::piece_table::PieceTable;
 pub use crate::point::Point;
 pub use crate::segment_bezier::SegmentBezier;
 pub use crate::segment_line::SegmentLine;
+pub use crate::stroke::{CapStyle, JoinStyle};
 
+pub mod boolean;
+pub mod calibration;
+pub mod export;
+pub mod import;
+pub mod job;
 pub mod registration_marks;
+pub mod svg_document;
 pub mod svg_path;
+pub mod thumbnail;
 
 mod alignment_data;
+mod convert_mode;
 mod cut_data;
 mod encode;
 mod error;
 mod fcm_file;
+mod fcm_metadata;
 mod file_header;
+mod file_source;
+mod file_summary;
 mod file_type;
 mod file_variant;
 mod generator;
+mod geometry;
+mod json;
+mod mark_scaling;
 mod outline;
 mod outline_tag;
 mod path;
 mod path_shape;
 mod path_tool;
 mod piece;
+mod piece_builder;
 mod piece_restrictions;
 mod piece_table;
 mod point;
 mod segment_bezier;
 mod segment_line;
+mod stroke;
 mod util;
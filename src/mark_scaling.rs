@@ -0,0 +1,17 @@
+/// What [`FcmFile::scale`] does with the cut area and registration marks.
+/// Marks that don't match the printed sheet make the machine cut off the
+/// artwork without any error, so this has to follow how the print changed.
+///
+/// [`FcmFile::scale`]: crate::FcmFile::scale
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MarkScaling {
+    /// Leave the cut area and marks as they are, for scaling the cut lines on
+    /// a sheet that is printed unchanged
+    Keep,
+    /// Scale the cut area and mark positions with the pieces, for a sheet
+    /// printed scaled as a whole, like with a printer's fit to page option
+    Scale,
+    /// Scale the cut area and put the marks at their standard positions for
+    /// it, for a sheet printed at the new size with freshly placed marks
+    Recompute,
+}
@@ -6,17 +6,255 @@ use nom::number::complete::le_u32;
 use nom::IResult;
 
 use crate::encode::Encode;
+use crate::geometry;
 use crate::outline_tag::OutlineTag;
+use crate::point::Point;
 use crate::segment_bezier::SegmentBezier;
 use crate::segment_line::SegmentLine;
 use crate::{outline_tag, segment_bezier, segment_line};
 
+/// A run of segments of one kind. The format only knows lines and cubic
+/// beziers, tagged 0 and 1; there is no quadratic outline, so converting
+/// cubics to quadratics can't make files smaller. Quadratics from SVG are
+/// stored as the equivalent cubic, which is exact.
 #[derive(Debug, Clone)]
 pub enum Outline {
     Line(Vec<SegmentLine>),
     Bezier(Vec<SegmentBezier>),
 }
 
+impl Outline {
+    /// Counter-clockwise circle made of four bezier quarter arcs, starting and
+    /// ending at `(center.x + radius, center.y)`
+    pub fn circle(center: Point, radius: i32) -> Outline {
+        Outline::circle_with_segments(center, radius, 4)
+    }
+
+    /// Circle made of `segments` bezier arcs (at least two), starting and
+    /// ending at `(center.x + radius, center.y)`. More segments follow the
+    /// circle more closely, which matters for very large circles.
+    pub fn circle_with_segments(center: Point, radius: i32, segments: usize) -> Outline {
+        let segments = segments.max(2);
+        let (cx, cy) = geometry::to_f64(center);
+        let r = radius as f64;
+        let step = 2.0 * std::f64::consts::PI / segments as f64;
+        // Control point distance for a bezier approximating an arc of `step`
+        let k = 4.0 / 3.0 * (step / 4.0).tan() * r;
+
+        Outline::Bezier(
+            (0..segments)
+                .map(|i| {
+                    let (sin0, cos0) = (i as f64 * step).sin_cos();
+                    let (sin1, cos1) = ((i + 1) as f64 * step).sin_cos();
+                    SegmentBezier {
+                        control1: geometry::from_f64(
+                            cx + r * cos0 - k * sin0,
+                            cy + r * sin0 + k * cos0,
+                        ),
+                        control2: geometry::from_f64(
+                            cx + r * cos1 + k * sin1,
+                            cy + r * sin1 - k * cos1,
+                        ),
+                        end: if i + 1 == segments {
+                            Point {
+                                x: center.x + radius,
+                                y: center.y,
+                            }
+                        } else {
+                            geometry::from_f64(cx + r * cos1, cy + r * sin1)
+                        },
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Number of segments in this outline
+    pub fn len(&self) -> usize {
+        match self {
+            Outline::Line(segments) => segments.len(),
+            Outline::Bezier(segments) => segments.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// End point of the last segment, if any
+    pub fn end_point(&self) -> Option<Point> {
+        match self {
+            Outline::Line(segments) => segments.last().map(|it| it.end),
+            Outline::Bezier(segments) => segments.last().map(|it| it.end),
+        }
+    }
+
+    /// Split the outline into consecutive outlines of at most `max_segments`
+    /// segments each, for firmware that can't handle long outlines. Each part
+    /// continues from the end of the one before it. A limit of 0 is treated
+    /// as 1, and an outline without segments gives no parts.
+    pub fn chunk(&self, max_segments: usize) -> Vec<Outline> {
+        let mut parts = vec![];
+        let mut rest = self.clone();
+        while !rest.is_empty() {
+            let (part, tail) = rest.split_at(max_segments.max(1));
+            parts.push(part);
+            rest = tail;
+        }
+        parts
+    }
+
+    /// The first `n` segments, or all if there are fewer, and the rest
+    pub(crate) fn split_at(self, n: usize) -> (Outline, Outline) {
+        match self {
+            Outline::Line(mut segments) => {
+                let tail = segments.split_off(n.min(segments.len()));
+                (Outline::Line(segments), Outline::Line(tail))
+            }
+            Outline::Bezier(mut segments) => {
+                let tail = segments.split_off(n.min(segments.len()));
+                (Outline::Bezier(segments), Outline::Bezier(tail))
+            }
+        }
+    }
+
+    /// Add the segments of `other` to the end of this outline. Outlines don't
+    /// store where they start, so `other` continues from this outline's end.
+    /// Mixing line and bezier segments turns the result into a Bezier outline.
+    pub fn append(&mut self, other: Outline) {
+        match (&mut *self, other) {
+            (Outline::Line(segments), Outline::Line(other)) => segments.extend(other),
+            (Outline::Bezier(segments), other) => segments.extend(other.into_beziers()),
+            (Outline::Line(_), other) => {
+                let mut segments = std::mem::replace(self, Outline::Line(vec![])).into_beziers();
+                segments.extend(other.into_beziers());
+                *self = Outline::Bezier(segments);
+            }
+        }
+    }
+
+    /// Append `other`, which begins at `start`, joining it with a straight
+    /// line if this outline doesn't already end there
+    pub fn connect(&mut self, start: Point, other: Outline) {
+        if self
+            .end_point()
+            .is_some_and(|end| (end.x, end.y) != (start.x, start.y))
+        {
+            self.append(Outline::Line(vec![SegmentLine { end: start }]));
+        }
+        self.append(other);
+    }
+
+    /// Whether the outline, beginning at `start`, ends within `epsilon_fcm`
+    /// units of it on each axis. This only looks at the points, not at the
+    /// `PATH_OPEN` flag of the path. Outlines without segments are not closed.
+    pub fn is_geometrically_closed(&self, start: Point, epsilon_fcm: i32) -> bool {
+        self.end_point().is_some_and(|end| {
+            (end.x - start.x).abs() <= epsilon_fcm && (end.y - start.y).abs() <= epsilon_fcm
+        })
+    }
+
+    /// Make the outline end at `start`, the point it begins from, by adding a
+    /// straight segment unless it already ends within `epsilon_fcm` units of
+    /// it on each axis. An end that is within the tolerance but not exact is
+    /// moved onto `start`. Returns whether a segment was added.
+    pub fn ensure_closed(&mut self, start: Point, epsilon_fcm: i32) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        if !self.is_geometrically_closed(start, epsilon_fcm) {
+            self.append(Outline::Line(vec![SegmentLine { end: start }]));
+            return true;
+        }
+        match self {
+            Outline::Line(segments) => segments.last_mut().unwrap().end = start,
+            Outline::Bezier(segments) => segments.last_mut().unwrap().end = start,
+        }
+        false
+    }
+
+    /// Bezier segments of this outline, with lines as degenerate curves
+    fn into_beziers(self) -> Vec<SegmentBezier> {
+        match self {
+            Outline::Line(segments) => segments
+                .into_iter()
+                .map(|it| SegmentBezier {
+                    control1: it.end,
+                    control2: it.end,
+                    end: it.end,
+                })
+                .collect(),
+            Outline::Bezier(segments) => segments,
+        }
+    }
+
+    /// Apply `f` to every point of this outline, including bezier control points
+    pub fn map_points<F: FnMut(Point) -> Point>(&mut self, mut f: F) {
+        match self {
+            Outline::Line(segments) => {
+                for segment in segments {
+                    segment.end = f(segment.end);
+                }
+            }
+            Outline::Bezier(segments) => {
+                for segment in segments {
+                    segment.control1 = f(segment.control1);
+                    segment.control2 = f(segment.control2);
+                    segment.end = f(segment.end);
+                }
+            }
+        }
+    }
+
+    /// All points of this outline, including bezier control points
+    pub fn points(&self) -> Vec<Point> {
+        match self {
+            Outline::Line(segments) => segments.iter().map(|it| it.end).collect(),
+            Outline::Bezier(segments) => segments
+                .iter()
+                .flat_map(|it| [it.control1, it.control2, it.end])
+                .collect(),
+        }
+    }
+
+    /// Convert this outline into a Line outline, approximating beziers within
+    /// `tolerance_fcm` units. `start` is the point the outline begins at.
+    pub fn flatten(&self, start: Point, tolerance_fcm: i32) -> Outline {
+        match self {
+            Outline::Line(segments) => Outline::Line(segments.clone()),
+            Outline::Bezier(segments) => {
+                let mut current = start;
+                let mut lines = Vec::new();
+                for segment in segments {
+                    for end in geometry::flatten_cubic(current, segment, tolerance_fcm) {
+                        lines.push(SegmentLine { end });
+                    }
+                    current = segment.end;
+                }
+                Outline::Line(lines)
+            }
+        }
+    }
+
+    /// Length of the outline in mm, starting at `start`. Beziers are
+    /// flattened within 0.05mm, which measures them slightly short.
+    pub fn length(&self, start: Point) -> f64 {
+        let Outline::Line(segments) = self.flatten(start, geometry::DEFAULT_TOLERANCE) else {
+            return 0.0;
+        };
+        let mut current = start;
+        let mut length = 0.0;
+        for segment in segments {
+            length += geometry::length(
+                (segment.end.x - current.x) as f64,
+                (segment.end.y - current.y) as f64,
+            );
+            current = segment.end;
+        }
+        length / 100.0
+    }
+}
+
 pub(crate) fn read_outline(input: &[u8]) -> IResult<&[u8], Outline> {
     flat_map(outline_tag::read_outline_tag, |tag| match tag {
         OutlineTag::Line => read_outline_line,
@@ -59,3 +297,185 @@ impl Encode for Outline {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(points: &[(i32, i32)]) -> Outline {
+        Outline::Line(
+            points
+                .iter()
+                .map(|&(x, y)| SegmentLine {
+                    end: Point { x, y },
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_circle() {
+        let center = Point { x: 1000, y: 1000 };
+        let outline = Outline::circle(center, 500);
+        assert_eq!(outline.len(), 4);
+        let Outline::Bezier(segments) = &outline else {
+            panic!("Expected bezier outline");
+        };
+        // Standard quarter arc constant 0.5523 * 500
+        assert_eq!(
+            (segments[0].control1.x, segments[0].control1.y),
+            (1500, 1276)
+        );
+        assert_eq!((segments[0].end.x, segments[0].end.y), (1000, 1500));
+        assert_eq!(outline.end_point().map(|p| (p.x, p.y)), Some((1500, 1000)));
+    }
+
+    #[test]
+    fn test_circle_with_segments() {
+        let center = Point { x: 0, y: 0 };
+        let outline = Outline::circle_with_segments(center, 10000, 8);
+        assert_eq!(outline.len(), 8);
+
+        // Every flattened point stays close to the circle
+        for point in outline.flatten(Point { x: 10000, y: 0 }, 1).points() {
+            let distance = geometry::length(point.x as f64, point.y as f64);
+            assert!((distance - 10000.0).abs() <= 2.0);
+        }
+    }
+
+    #[test]
+    fn test_append_lines() {
+        let mut outline = line(&[(10, 0), (10, 10)]);
+        outline.append(line(&[(0, 10), (0, 0)]));
+
+        assert!(matches!(outline, Outline::Line(_)));
+        assert_eq!(outline.len(), 4);
+        assert_eq!(outline.end_point().map(|p| (p.x, p.y)), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_append_mixed() {
+        let mut outline = line(&[(10, 0)]);
+        outline.append(Outline::Bezier(vec![SegmentBezier {
+            control1: Point { x: 15, y: 0 },
+            control2: Point { x: 20, y: 5 },
+            end: Point { x: 20, y: 10 },
+        }]));
+
+        match &outline {
+            Outline::Bezier(segments) => {
+                assert_eq!(segments.len(), 2);
+                assert_eq!(segments[0].control1.x, 10);
+            }
+            _ => panic!("Expected bezier outline"),
+        }
+    }
+
+    #[test]
+    fn test_connect() {
+        let mut outline = line(&[(10, 0)]);
+        outline.connect(Point { x: 20, y: 0 }, line(&[(30, 0)]));
+        assert_eq!(outline.len(), 3);
+
+        outline.connect(Point { x: 30, y: 0 }, line(&[(40, 0)]));
+        assert_eq!(outline.len(), 4);
+    }
+
+    #[test]
+    fn test_unknown_outline_tag() {
+        let mut bytes = 2u32.to_le_bytes().to_vec();
+        bytes.extend(0u32.to_le_bytes());
+        assert!(read_outline(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_line_length() {
+        let start = Point { x: 0, y: 0 };
+        assert_eq!(line(&[(300, 0), (300, 400), (0, 0)]).length(start), 12.0);
+        assert_eq!(line(&[]).length(start), 0.0);
+    }
+
+    #[test]
+    fn test_bezier_length() {
+        // Quarter circle with a 10mm radius
+        let k = 552;
+        let arc = Outline::Bezier(vec![SegmentBezier {
+            control1: Point { x: 1000, y: k },
+            control2: Point { x: k, y: 1000 },
+            end: Point { x: 0, y: 1000 },
+        }]);
+        let length = arc.length(Point { x: 1000, y: 0 });
+        assert!(
+            (length - std::f64::consts::FRAC_PI_2 * 10.0).abs() < 0.05,
+            "{}",
+            length
+        );
+    }
+
+    #[test]
+    fn test_chunk() {
+        let points: Vec<(i32, i32)> = (1..=1000).map(|i| (i * 10, i % 2)).collect();
+        let outline = line(&points);
+        let parts = outline.chunk(256);
+        let lengths: Vec<_> = parts.iter().map(Outline::len).collect();
+        assert_eq!(lengths, vec![256, 256, 256, 232]);
+
+        // The parts join up again into the original
+        let mut joined = line(&[]);
+        for part in parts {
+            joined.append(part);
+        }
+        assert_eq!(joined.points().len(), 1000);
+        assert!(joined
+            .points()
+            .iter()
+            .zip(&points)
+            .all(|(p, q)| (p.x, p.y) == *q));
+
+        assert_eq!(outline.chunk(1000).len(), 1);
+        assert!(line(&[]).chunk(256).is_empty());
+    }
+
+    #[test]
+    fn test_is_geometrically_closed() {
+        let start = Point { x: 0, y: 0 };
+        let square = line(&[(100, 0), (100, 100), (0, 100), (0, 0)]);
+        assert!(square.is_geometrically_closed(start, 0));
+
+        let open = line(&[(100, 0), (100, 100), (0, 100)]);
+        assert!(!open.is_geometrically_closed(start, 0));
+        assert!(!open.is_geometrically_closed(start, 99));
+        assert!(open.is_geometrically_closed(start, 100));
+
+        assert!(!line(&[]).is_geometrically_closed(start, 0));
+    }
+
+    #[test]
+    fn test_ensure_closed() {
+        let start = Point { x: 0, y: 0 };
+        let mut open = line(&[(100, 0), (100, 100)]);
+        assert!(open.ensure_closed(start, 0));
+        assert_eq!(open.len(), 3);
+        assert_eq!(open.end_point().map(|p| (p.x, p.y)), Some((0, 0)));
+
+        // Already closed, nothing to add
+        assert!(!open.ensure_closed(start, 0));
+        assert_eq!(open.len(), 3);
+
+        // Within tolerance the end is moved onto the start instead
+        let mut nearly = line(&[(100, 0), (100, 100), (1, -1)]);
+        assert!(!nearly.ensure_closed(start, 1));
+        assert_eq!(nearly.len(), 3);
+        assert_eq!(nearly.end_point().map(|p| (p.x, p.y)), Some((0, 0)));
+
+        let mut curve = Outline::circle(Point { x: 0, y: 0 }, 100);
+        curve.map_points(|p| Point {
+            x: p.x,
+            y: p.y + 50,
+        });
+        assert!(curve.ensure_closed(Point { x: 100, y: 0 }, 0));
+        assert!(matches!(curve, Outline::Bezier(ref segments) if segments.len() == 5));
+
+        assert!(!line(&[]).ensure_closed(start, 0));
+    }
+}
@@ -8,12 +8,27 @@ use nom::sequence::tuple;
 use nom::IResult;
 
 use crate::encode::Encode;
+use crate::outline::Outline;
 use crate::path_shape::PathShape;
 use crate::path_tool::PathTool;
 use crate::point::{read_point, Point};
 use crate::{path_shape, path_tool};
 
-#[derive(Debug)]
+/// A single contour with one tool.
+///
+/// The format stores the tool per path, not per outline: all outlines of a
+/// path chain into one contour that is handled by the same tool. Shapes that
+/// mix tools, like a cut boundary with a drawn detail, are separate paths, see
+/// [`Path::split_by_tool`].
+///
+/// A shape can't be reduced to a single location: the machine has nothing to
+/// cut for a shape without extent, and [`FcmFile::validate`] rejects it. Such
+/// paths are still read and written as they are, so files round-trip. Point
+/// actions are expressed with rhinestone positions instead, see
+/// [`Path::dots`].
+///
+/// [`FcmFile::validate`]: crate::FcmFile::validate
+#[derive(Debug, Clone)]
 pub struct Path {
     pub tool: PathTool,
     pub shape: Option<PathShape>,
@@ -21,6 +36,155 @@ pub struct Path {
     pub rhinestones: Vec<Point>,
 }
 
+impl Path {
+    /// A path marking `points` without any outline, stored the way Canvas
+    /// Workspace stores rhinestone templates. The machine handles each point
+    /// as a small circle of `diameter_fcm`.
+    pub fn dots(points: Vec<Point>, diameter_fcm: u32) -> Path {
+        Path {
+            tool: PathTool::TOOL_CUT | PathTool::TOOL_DRAW | PathTool::TOOL_RHINESTONE,
+            shape: None,
+            rhinestone_diameter: Some(diameter_fcm),
+            rhinestones: points,
+        }
+    }
+
+    /// Split a contour starting at `start` whose outlines are annotated with
+    /// tools into one path per run of outlines sharing the same tool.
+    ///
+    /// Each path starts where the previous one ended. `PATH_OPEN` is derived
+    /// from the geometry of each resulting path rather than taken from the
+    /// annotations.
+    pub fn split_by_tool(start: Point, outlines: Vec<(PathTool, Outline)>) -> Vec<Path> {
+        let mut paths: Vec<Path> = Vec::new();
+        let mut current = start;
+        let mut previous_tool = None;
+
+        for (tool, outline) in outlines {
+            let tool = tool - PathTool::PATH_OPEN;
+            let end = outline.end_point().unwrap_or(current);
+            match paths.last_mut().and_then(|it| it.shape.as_mut()) {
+                Some(shape) if previous_tool == Some(tool) => shape.outlines.push(outline),
+                _ => paths.push(Path {
+                    tool,
+                    shape: Some(PathShape {
+                        start: current,
+                        outlines: vec![outline],
+                    }),
+                    rhinestone_diameter: None,
+                    rhinestones: vec![],
+                }),
+            }
+            previous_tool = Some(tool);
+            current = end;
+        }
+
+        for path in &mut paths {
+            if let Some(shape) = &path.shape {
+                let end = shape.end_point();
+                if (end.x, end.y) != (shape.start.x, shape.start.y) {
+                    path.tool |= PathTool::PATH_OPEN;
+                }
+            }
+        }
+        paths
+    }
+
+    /// Split the path into paths of at most `max_segments` segments each, for
+    /// firmware that can't handle paths with many points. Each part starts
+    /// where the previous one ended, and `PATH_OPEN` is set on parts that
+    /// don't end where they start. Rhinestones stay with the first part and
+    /// paths without a shape are returned as they are.
+    pub fn chunk(&self, max_segments: usize) -> Vec<Path> {
+        let Some(shape) = &self.shape else {
+            return vec![self.clone()];
+        };
+        let max_segments = max_segments.max(1);
+        let tool = self.tool - PathTool::PATH_OPEN;
+
+        let mut paths: Vec<Path> = vec![];
+        let mut current = shape.start;
+        let mut count = max_segments;
+        for outline in &shape.outlines {
+            let mut rest = outline.clone();
+            while !rest.is_empty() {
+                if count == max_segments {
+                    paths.push(Path {
+                        tool,
+                        shape: Some(PathShape {
+                            start: current,
+                            outlines: vec![],
+                        }),
+                        rhinestone_diameter: self.rhinestone_diameter,
+                        rhinestones: if paths.is_empty() {
+                            self.rhinestones.clone()
+                        } else {
+                            vec![]
+                        },
+                    });
+                    count = 0;
+                }
+                let (part, tail) = rest.split_at(max_segments - count);
+                count += part.len();
+                current = part.end_point().unwrap_or(current);
+                if let Some(shape) = paths.last_mut().and_then(|it| it.shape.as_mut()) {
+                    shape.outlines.push(part);
+                }
+                rest = tail;
+            }
+        }
+        if paths.is_empty() {
+            return vec![self.clone()];
+        }
+
+        for path in &mut paths {
+            if path.shape.as_ref().is_some_and(|shape| !shape.is_closed()) {
+                path.tool |= PathTool::PATH_OPEN;
+            }
+        }
+        paths
+    }
+
+    /// Apply `f` to every point of this path, including rhinestone positions
+    pub fn map_points<F: FnMut(Point) -> Point>(&mut self, mut f: F) {
+        if let Some(shape) = &mut self.shape {
+            shape.map_points(&mut f);
+        }
+        for rhinestone in &mut self.rhinestones {
+            *rhinestone = f(*rhinestone);
+        }
+    }
+
+    /// Move every point of this path by the given offset
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        self.map_points(|p| Point {
+            x: p.x + dx,
+            y: p.y + dy,
+        });
+    }
+
+    /// Minimum and maximum corner of the box containing the shape and rhinestones
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let mut bounds = self.shape.as_ref().map(PathShape::bounds);
+        for rhinestone in &self.rhinestones {
+            bounds = Some(match bounds {
+                None => (*rhinestone, *rhinestone),
+                Some((min, max)) => (
+                    Point {
+                        x: min.x.min(rhinestone.x),
+                        y: min.y.min(rhinestone.y),
+                    },
+                    Point {
+                        x: max.x.max(rhinestone.x),
+                        y: max.y.max(rhinestone.y),
+                    },
+                ),
+            });
+        }
+        bounds
+    }
+}
+
 fn read_rhinestone_diameter(input: &[u8]) -> IResult<&[u8], Option<u32>> {
     map(le_u32, |diameter| {
         if diameter == 0x3f000000 {
@@ -85,3 +249,123 @@ impl Encode for Path {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SegmentLine;
+
+    fn lines(points: &[(i32, i32)]) -> Outline {
+        Outline::Line(
+            points
+                .iter()
+                .map(|&(x, y)| SegmentLine {
+                    end: Point { x, y },
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_chunk() {
+        // A closed 1000-segment path, mixing line and bezier outlines
+        let bezier = Outline::Bezier(
+            (1..=399)
+                .map(|i| crate::SegmentBezier {
+                    control1: Point { x: 6000, y: i * 10 },
+                    control2: Point { x: 6000, y: i * 10 },
+                    end: Point {
+                        x: 6000 - i * 15,
+                        y: i * 10,
+                    },
+                })
+                .collect(),
+        );
+        let path = Path {
+            tool: PathTool::TOOL_CUT,
+            shape: Some(PathShape {
+                start: Point { x: 0, y: 0 },
+                outlines: vec![
+                    lines(&(1..=600).map(|i| (i * 10, 0)).collect::<Vec<_>>()),
+                    bezier,
+                    lines(&[(0, 0)]),
+                ],
+            }),
+            rhinestone_diameter: None,
+            rhinestones: vec![],
+        };
+        assert!(path.shape.as_ref().unwrap().is_closed());
+
+        let parts = path.chunk(256);
+        assert_eq!(parts.len(), 4);
+        let mut start = (0, 0);
+        for (part, length) in parts.iter().zip([256, 256, 256, 232]) {
+            let shape = part.shape.as_ref().unwrap();
+            assert_eq!(
+                shape.outlines.iter().map(Outline::len).sum::<usize>(),
+                length
+            );
+            assert_eq!((shape.start.x, shape.start.y), start);
+            assert_eq!(part.tool, PathTool::TOOL_CUT | PathTool::PATH_OPEN);
+            start = (shape.end_point().x, shape.end_point().y);
+        }
+        assert_eq!(start, (0, 0));
+        // The third part runs from the line into the curve
+        assert_eq!(parts[2].shape.as_ref().unwrap().outlines.len(), 2);
+
+        let whole = path.chunk(1000);
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0].tool, PathTool::TOOL_CUT);
+    }
+
+    #[test]
+    fn test_split_by_tool() {
+        let paths = Path::split_by_tool(
+            Point { x: 0, y: 0 },
+            vec![
+                (PathTool::TOOL_CUT, lines(&[(1000, 0), (1000, 1000)])),
+                (PathTool::TOOL_CUT, lines(&[(0, 1000), (0, 0)])),
+                (PathTool::TOOL_DRAW, lines(&[(500, 500)])),
+            ],
+        );
+
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].tool, PathTool::TOOL_CUT);
+        assert_eq!(paths[0].shape.as_ref().unwrap().outlines.len(), 2);
+
+        assert_eq!(paths[1].tool, PathTool::TOOL_DRAW | PathTool::PATH_OPEN);
+        let detail = paths[1].shape.as_ref().unwrap();
+        assert_eq!((detail.start.x, detail.start.y), (0, 0));
+        assert_eq!((detail.end_point().x, detail.end_point().y), (500, 500));
+    }
+
+    #[test]
+    fn test_single_point_round_trip() {
+        let path = Path {
+            tool: PathTool::TOOL_CUT | PathTool::PATH_OPEN,
+            shape: Some(PathShape {
+                start: Point { x: 100, y: 100 },
+                outlines: vec![lines(&[(100, 100)])],
+            }),
+            rhinestone_diameter: None,
+            rhinestones: vec![],
+        };
+        let bytes = path.encode_to_vec().unwrap();
+
+        let (rest, parsed) = read_path(&bytes).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(parsed.encode_to_vec().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_dots_round_trip() {
+        let path = Path::dots(vec![Point { x: 0, y: 0 }, Point { x: 500, y: 0 }], 180);
+        let bytes = path.encode_to_vec().unwrap();
+
+        let (rest, parsed) = read_path(&bytes).unwrap();
+        assert!(rest.is_empty());
+        assert!(parsed.shape.is_none());
+        assert_eq!(parsed.rhinestone_diameter, Some(180));
+        assert_eq!(parsed.rhinestones.len(), 2);
+    }
+}
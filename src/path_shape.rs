@@ -5,13 +5,454 @@ use nom::IResult;
 
 use crate::outline::{read_outline, Outline};
 use crate::point::{read_point, Point};
+use crate::segment_line::SegmentLine;
+use crate::stroke::{self, CapStyle, JoinStyle};
+use crate::{boolean, geometry};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PathShape {
     pub start: Point,
     pub outlines: Vec<Outline>,
 }
 
+impl PathShape {
+    /// Build a Line shape through `points`, which are already in FCM units
+    /// (hundredths of mm), without going through the SVG conversion. Closed
+    /// shapes get a segment back to the first point if they don't already
+    /// end there. No points give an empty shape at the origin.
+    pub fn from_fcm_points(points: &[Point], closed: bool) -> PathShape {
+        match points {
+            [] => PathShape {
+                start: Point::default(),
+                outlines: vec![],
+            },
+            _ if closed => geometry::polygon_to_shape(points),
+            [start, rest @ ..] => PathShape {
+                start: *start,
+                outlines: vec![Outline::Line(
+                    rest.iter().map(|&end| SegmentLine { end }).collect(),
+                )],
+            },
+        }
+    }
+
+    /// Point the last outline ends at, or the start if there are no segments
+    pub fn end_point(&self) -> Point {
+        self.outlines
+            .iter()
+            .rev()
+            .find_map(Outline::end_point)
+            .unwrap_or(self.start)
+    }
+
+    /// Apply `f` to every point of this shape, including bezier control points
+    pub fn map_points<F: FnMut(Point) -> Point>(&mut self, mut f: F) {
+        self.start = f(self.start);
+        for outline in &mut self.outlines {
+            outline.map_points(&mut f);
+        }
+    }
+
+    /// Split the shape into one shape per outline. Outlines continue from
+    /// where the previous one ended, so each shape starts at the end of the
+    /// one before it.
+    ///
+    /// Outlines are runs of line or bezier segments within one contour; holes
+    /// and the separate parts of compound shapes are stored as separate paths
+    /// and don't need exploding.
+    pub fn explode(self) -> Vec<PathShape> {
+        let mut start = self.start;
+        self.outlines
+            .into_iter()
+            .map(|outline| {
+                let shape = PathShape {
+                    start,
+                    outlines: vec![outline],
+                };
+                start = shape.end_point();
+                shape
+            })
+            .collect()
+    }
+
+    /// Whether the shape ends where it starts
+    pub fn is_closed(&self) -> bool {
+        let end = self.end_point();
+        !self.outlines.iter().all(Outline::is_empty)
+            && end.x == self.start.x
+            && end.y == self.start.y
+    }
+
+    /// Move every point of this shape by the given offset
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        self.map_points(|p| Point {
+            x: p.x + dx,
+            y: p.y + dy,
+        });
+    }
+
+    /// Scale the shape uniformly so its bounds are `target_mm` wide. The top
+    /// left corner of the bounds stays in place. Shapes without width are
+    /// left unchanged.
+    pub fn fit_to_width_mm(&mut self, target_mm: f64) {
+        let (min, max) = self.bounds();
+        self.scale_from(min, target_mm * 100.0, max.x - min.x);
+    }
+
+    /// Scale the shape uniformly so its bounds are `target_mm` high. The top
+    /// left corner of the bounds stays in place. Shapes without height are
+    /// left unchanged.
+    pub fn fit_to_height_mm(&mut self, target_mm: f64) {
+        let (min, max) = self.bounds();
+        self.scale_from(min, target_mm * 100.0, max.y - min.y);
+    }
+
+    /// Scale every point away from `origin` by `target / current`
+    fn scale_from(&mut self, origin: Point, target: f64, current: i32) {
+        if current <= 0 {
+            return;
+        }
+        let factor = target / current as f64;
+        self.map_points(|p| Point {
+            x: origin.x + ((p.x - origin.x) as f64 * factor).round() as i32,
+            y: origin.y + ((p.y - origin.y) as f64 * factor).round() as i32,
+        });
+    }
+
+    /// Replace every Bezier outline with a Line outline approximating it
+    /// within `tolerance_fcm` units
+    pub fn flatten(&mut self, tolerance_fcm: i32) {
+        let mut current = self.start;
+        for outline in &mut self.outlines {
+            let end = outline.end_point().unwrap_or(current);
+            *outline = outline.flatten(current, tolerance_fcm);
+            current = end;
+        }
+    }
+
+    /// Round every point to the nearest multiple of `grid_fcm`, returning how
+    /// many segments collapsed to zero length because of it
+    pub fn snap_to_grid(&mut self, grid_fcm: i32) -> usize {
+        let same = |a: Point, b: Point| a.x == b.x && a.y == b.y;
+        let mut collapsed = 0;
+        let mut previous = self.start;
+        self.start = geometry::snap_point(self.start, grid_fcm);
+
+        let mut snapped_previous = self.start;
+        for outline in &mut self.outlines {
+            match outline {
+                Outline::Line(segments) => {
+                    for segment in segments {
+                        let end = geometry::snap_point(segment.end, grid_fcm);
+                        if same(end, snapped_previous) && !same(segment.end, previous) {
+                            collapsed += 1;
+                        }
+                        previous = segment.end;
+                        segment.end = end;
+                        snapped_previous = end;
+                    }
+                }
+                Outline::Bezier(segments) => {
+                    for segment in segments {
+                        let points = [segment.control1, segment.control2, segment.end];
+                        let snapped = points.map(|it| geometry::snap_point(it, grid_fcm));
+                        if snapped.iter().all(|it| same(*it, snapped_previous))
+                            && !points.iter().all(|it| same(*it, previous))
+                        {
+                            collapsed += 1;
+                        }
+                        previous = segment.end;
+                        [segment.control1, segment.control2, segment.end] = snapped;
+                        snapped_previous = segment.end;
+                    }
+                }
+            }
+        }
+        collapsed
+    }
+
+    /// Remove outlines that don't go anywhere: ones without segments and ones
+    /// whose points all sit where the previous outline ended, as left behind
+    /// by snapping, boolean or offset operations. The rest of the contour is
+    /// unchanged. Returns how many outlines were removed.
+    pub fn prune_empty_outlines(&mut self) -> usize {
+        let count = self.outlines.len();
+        let mut current = self.start;
+        self.outlines.retain(|outline| {
+            let keep = outline
+                .points()
+                .iter()
+                .any(|p| p.x != current.x || p.y != current.y);
+            current = outline.end_point().unwrap_or(current);
+            keep
+        });
+        count - self.outlines.len()
+    }
+
+    /// Minimum and maximum corner of the box containing all points,
+    /// including bezier control points
+    pub fn bounds(&self) -> (Point, Point) {
+        let mut min = self.start;
+        let mut max = self.start;
+        for point in self.outlines.iter().flat_map(Outline::points) {
+            min.x = min.x.min(point.x);
+            min.y = min.y.min(point.y);
+            max.x = max.x.max(point.x);
+            max.y = max.y.max(point.y);
+        }
+        (min, max)
+    }
+
+    /// All points along the shape, starting with `start`, with beziers
+    /// approximated within `tolerance_fcm` units
+    pub fn to_polyline(&self, tolerance_fcm: i32) -> Vec<Point> {
+        let mut points = vec![self.start];
+        for outline in &self.outlines {
+            let from = points[points.len() - 1];
+            if let Outline::Line(segments) = outline.flatten(from, tolerance_fcm) {
+                points.extend(segments.iter().map(|it| it.end));
+            }
+        }
+        points
+    }
+
+    /// Length of all outlines in mm, see [`Outline::length`]
+    pub fn perimeter(&self) -> f64 {
+        let mut current = self.start;
+        let mut perimeter = 0.0;
+        for outline in &self.outlines {
+            perimeter += outline.length(current);
+            current = outline.end_point().unwrap_or(current);
+        }
+        perimeter
+    }
+
+    /// Area enclosed by the shape in FCM units², positive for counter-clockwise
+    /// winding (in a y-up frame) and negative for clockwise
+    pub fn signed_area(&self) -> f64 {
+        let points = self.to_polyline(geometry::DEFAULT_TOLERANCE);
+        let mut area = 0.0;
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            area += a.x as f64 * b.y as f64 - b.x as f64 * a.y as f64;
+        }
+        area / 2.0
+    }
+
+    /// Whether both shapes trace the same contour with every vertex within
+    /// `epsilon_fcm` units on each axis. Closed shapes may start at a
+    /// different vertex or run in the opposite direction; open shapes may be
+    /// reversed. Beziers are compared after flattening.
+    pub fn approx_eq(&self, other: &PathShape, epsilon_fcm: i32) -> bool {
+        let (a, a_closed) = self.vertices();
+        let (b, b_closed) = other.vertices();
+        if a.len() != b.len() || a_closed != b_closed {
+            return false;
+        }
+        let near = |p: Point, q: Point| {
+            (p.x - q.x).abs() <= epsilon_fcm && (p.y - q.y).abs() <= epsilon_fcm
+        };
+        let n = a.len();
+        let offsets = if a_closed { 0..n } else { 0..1 };
+
+        for offset in offsets {
+            let forward = (0..n).all(|i| near(a[i], b[(i + offset) % n]));
+            let backward = (0..n).all(|i| near(a[i], b[(n + offset - i) % n]));
+            let reversed = !a_closed && (0..n).all(|i| near(a[i], b[n - 1 - i]));
+            if forward || (a_closed && backward) || reversed {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Flattened points without repeats, and whether the shape returns to its
+    /// start, in which case the closing point is left out
+    fn vertices(&self) -> (Vec<Point>, bool) {
+        let mut points = self.to_polyline(geometry::DEFAULT_TOLERANCE);
+        points.dedup_by(|a, b| a.x == b.x && a.y == b.y);
+        let (first, last) = (points[0], points[points.len() - 1]);
+        let closed = points.len() > 1 && first.x == last.x && first.y == last.y;
+        if closed {
+            points.pop();
+        }
+        (points, closed)
+    }
+
+    /// Whether the flattened shape, closed back to its start, bounds a convex
+    /// region: every corner turns the same way and the outline goes around
+    /// only once. Collinear points are ignored.
+    pub fn is_convex(&self) -> bool {
+        let (points, _) = self.vertices();
+        if points.len() < 3 {
+            return false;
+        }
+
+        let mut sign = 0.0;
+        let mut turning = 0.0;
+        for i in 0..points.len() {
+            let (a, b, c) = (
+                geometry::to_f64(points[i]),
+                geometry::to_f64(points[(i + 1) % points.len()]),
+                geometry::to_f64(points[(i + 2) % points.len()]),
+            );
+            let (u, v) = ((b.0 - a.0, b.1 - a.1), (c.0 - b.0, c.1 - b.1));
+            let cross = u.0 * v.1 - u.1 * v.0;
+            if cross == 0.0 {
+                continue;
+            }
+            if sign == 0.0 {
+                sign = cross.signum();
+            } else if cross.signum() != sign {
+                return false;
+            }
+            turning += cross.atan2(u.0 * v.0 + u.1 * v.1);
+        }
+
+        // A star polygon turns consistently but winds around more than once
+        sign != 0.0 && turning.abs() < 3.0 * std::f64::consts::PI
+    }
+
+    /// Whether `point` lies inside the flattened shape, closed back to its
+    /// start, using the even-odd rule. A contour that runs around a hole and
+    /// back out along a bridge leaves the hole outside. Holes stored as
+    /// separate paths have to be tested separately.
+    pub fn contains_point(&self, point: Point) -> bool {
+        let points = self.to_polyline(geometry::DEFAULT_TOLERANCE);
+        let (px, py) = geometry::to_f64(point);
+        let mut inside = false;
+        for (i, a) in points.iter().enumerate() {
+            let (ax, ay) = geometry::to_f64(*a);
+            let (bx, by) = geometry::to_f64(points[(i + 1) % points.len()]);
+            if (ay > py) != (by > py) && px < ax + (py - ay) * (bx - ax) / (by - ay) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Corners of the smallest-area rectangle containing the flattened shape,
+    /// in order around the rectangle. The smallest rectangle has a side along
+    /// an edge of the convex hull, so each hull edge is tried in turn.
+    pub fn min_area_rect(&self) -> (Point, Point, Point, Point) {
+        let hull: Vec<(f64, f64)> =
+            geometry::convex_hull(&self.to_polyline(geometry::DEFAULT_TOLERANCE))
+                .into_iter()
+                .map(geometry::to_f64)
+                .collect();
+        let origin = hull[0];
+
+        // Start from the axis-aligned box, which also covers a single point
+        let mut best = (f64::INFINITY, (1.0, 0.0), [0.0; 4]);
+        let edges = (0..hull.len()).map(|i| (hull[i], hull[(i + 1) % hull.len()]));
+        for (a, b) in std::iter::once(((0.0, 0.0), (1.0, 0.0))).chain(edges) {
+            let length = geometry::length(b.0 - a.0, b.1 - a.1);
+            if length == 0.0 {
+                continue;
+            }
+            let u = ((b.0 - a.0) / length, (b.1 - a.1) / length);
+            // Extents along the edge direction and its normal
+            let mut extents = [
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ];
+            for p in &hull {
+                let (dx, dy) = (p.0 - origin.0, p.1 - origin.1);
+                let (along, across) = (dx * u.0 + dy * u.1, -dx * u.1 + dy * u.0);
+                extents = [
+                    extents[0].min(along),
+                    extents[1].max(along),
+                    extents[2].min(across),
+                    extents[3].max(across),
+                ];
+            }
+            let area = (extents[1] - extents[0]) * (extents[3] - extents[2]);
+            if area < best.0 {
+                best = (area, u, extents);
+            }
+        }
+
+        let (_, u, [min_along, max_along, min_across, max_across]) = best;
+        let corner = |along: f64, across: f64| {
+            geometry::from_f64(
+                origin.0 + along * u.0 - across * u.1,
+                origin.1 + along * u.1 + across * u.0,
+            )
+        };
+        (
+            corner(min_along, min_across),
+            corner(max_along, min_across),
+            corner(max_along, max_across),
+            corner(min_along, max_across),
+        )
+    }
+
+    /// Contours of the shape grown outwards by `distance_fcm` units, or shrunk
+    /// inwards for negative distances, with outer corners finished by `join`.
+    /// The shape is treated as closed. Like [`boolean`] results, each contour
+    /// is a closed Line shape, outer contours wind counter-clockwise and holes
+    /// clockwise; shrinking can split a shape or make it disappear.
+    ///
+    /// A shape winding clockwise is taken to be a hole, as in [`boolean`]
+    /// results, and offset the other way: growing the material around it
+    /// shrinks the hole, which stays clockwise.
+    pub fn offset(&self, distance_fcm: i32, join: JoinStyle) -> Vec<PathShape> {
+        if self.signed_area() < 0.0 {
+            return self
+                .reversed()
+                .offset(-distance_fcm, join)
+                .iter()
+                .map(PathShape::reversed)
+                .collect();
+        }
+
+        let shape = std::slice::from_ref(self);
+        if distance_fcm == 0 {
+            return boolean::union(shape);
+        }
+
+        // Run past the start again so it gets a join like the other corners
+        let mut points = self.to_polyline(geometry::DEFAULT_TOLERANCE);
+        let (first, last) = (points[0], points[points.len() - 1]);
+        if (first.x, first.y) != (last.x, last.y) {
+            points.push(first);
+        }
+        if points.len() > 1 {
+            points.push(points[1]);
+        }
+        let band = stroke::stroke_polyline(&points, 2 * distance_fcm.abs(), CapStyle::Butt, join);
+
+        if distance_fcm > 0 {
+            // Normalize the windings first so the band and shape add up
+            let mut parts = boolean::union(shape);
+            parts.extend(boolean::union(&[band]));
+            boolean::union(&parts)
+        } else {
+            boolean::difference(shape, &[band])
+        }
+    }
+
+    /// The shape flattened and run backwards, as a closed Line shape
+    pub(crate) fn reversed(&self) -> PathShape {
+        let mut points = self.to_polyline(geometry::DEFAULT_TOLERANCE);
+        points.reverse();
+        geometry::polygon_to_shape(&points)
+    }
+
+    /// Turn this shape, treated as the centerline of a stroke, into the closed
+    /// outline of that stroke so both sides get cut
+    pub fn stroke_to_outline(&self, width_fcm: i32, cap: CapStyle, join: JoinStyle) -> PathShape {
+        stroke::stroke_polyline(
+            &self.to_polyline(geometry::DEFAULT_TOLERANCE),
+            width_fcm,
+            cap,
+            join,
+        )
+    }
+}
+
 pub fn read_path_shape<'a>(
     outline_count: usize,
 ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Option<PathShape>> {
@@ -23,3 +464,300 @@ pub fn read_path_shape<'a>(
         ),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SegmentLine;
+
+    fn polygon(points: &[(i32, i32)]) -> PathShape {
+        let points: Vec<Point> = points.iter().map(|&(x, y)| Point { x, y }).collect();
+        geometry::polygon_to_shape(&points)
+    }
+
+    #[test]
+    fn test_perimeter() {
+        let square = polygon(&[(0, 0), (1000, 0), (1000, 1000), (0, 1000)]);
+        assert!((square.perimeter() - 40.0).abs() < 1e-9);
+
+        // A line followed by a bezier continues from the line's end
+        let mut shape =
+            PathShape::from_fcm_points(&[Point { x: 0, y: 0 }, Point { x: 500, y: 0 }], false);
+        shape
+            .outlines
+            .push(Outline::Bezier(vec![crate::SegmentBezier {
+                control1: Point { x: 500, y: 0 },
+                control2: Point { x: 500, y: 0 },
+                end: Point { x: 500, y: 1000 },
+            }]));
+        assert!((shape.perimeter() - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rectangle_is_convex() {
+        assert!(polygon(&[(0, 0), (1000, 0), (1000, 500), (0, 500)]).is_convex());
+        // Collinear points and reversed winding don't matter
+        assert!(polygon(&[(0, 0), (0, 500), (500, 500), (1000, 500), (1000, 0)]).is_convex());
+    }
+
+    #[test]
+    fn test_l_shape_is_not_convex() {
+        let shape = polygon(&[
+            (0, 0),
+            (1000, 0),
+            (1000, 500),
+            (500, 500),
+            (500, 1000),
+            (0, 1000),
+        ]);
+        assert!(!shape.is_convex());
+    }
+
+    #[test]
+    fn test_star_is_not_convex() {
+        let shape = polygon(&[(0, 0), (600, 1800), (1200, 0), (-300, 1100), (1500, 1100)]);
+        assert!(!shape.is_convex());
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let a = polygon(&[(0, 0), (1000, 0), (1000, 1000), (0, 1000)]);
+        let b = polygon(&[(1, 1), (1001, 1), (1001, 1001), (1, 1001)]);
+        assert!(a.approx_eq(&b, 5));
+        assert!(!a.approx_eq(&b, 0));
+        assert!(a.approx_eq(&a, 0));
+
+        // Other start vertex, opposite direction
+        let c = polygon(&[(1000, 1000), (1000, 0), (0, 0), (0, 1000)]);
+        assert!(a.approx_eq(&c, 0));
+
+        let d = polygon(&[(0, 0), (1000, 0), (1000, 1000), (0, 900)]);
+        assert!(!a.approx_eq(&d, 5));
+    }
+
+    #[test]
+    fn test_min_area_rect() {
+        // A 1000 x 100 rectangle at 45 degrees, whose axis-aligned box is 778 x 778
+        let diagonal = polygon(&[(0, 0), (707, 707), (636, 778), (-71, 71), (0, 0)]);
+        let (a, b, c, d) = diagonal.min_area_rect();
+        let side = |p: Point, q: Point| geometry::length((q.x - p.x) as f64, (q.y - p.y) as f64);
+        let (width, height) = (side(a, b), side(b, c));
+        assert!(
+            (width * height - 100_000.0).abs() < 1000.0,
+            "{} x {}",
+            width,
+            height
+        );
+        assert!((side(c, d) - width).abs() < 2.0 && (side(d, a) - height).abs() < 2.0);
+
+        let (min, max) = diagonal.bounds();
+        assert!(width * height < ((max.x - min.x) * (max.y - min.y)) as f64 / 5.0);
+
+        // An axis-aligned square is its own rectangle
+        let square = polygon(&[(0, 0), (100, 0), (100, 50), (0, 50), (0, 0)]);
+        let corners = square.min_area_rect();
+        let xy = |p: Point| (p.x, p.y);
+        let mut corners = vec![xy(corners.0), xy(corners.1), xy(corners.2), xy(corners.3)];
+        corners.sort();
+        assert_eq!(corners, vec![(0, 0), (0, 50), (100, 0), (100, 50)]);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let square = polygon(&[(0, 0), (1000, 0), (1000, 1000), (0, 1000), (0, 0)]);
+
+        assert!(square.contains_point(Point { x: 500, y: 500 }));
+        assert!(!square.contains_point(Point { x: 1500, y: 500 }));
+        assert!(!square.contains_point(Point { x: 500, y: -1 }));
+    }
+
+    #[test]
+    fn test_contains_point_hole() {
+        // Outer square, a bridge in to the hole, around the hole and back out
+        let donut = polygon(&[
+            (0, 0),
+            (1000, 0),
+            (1000, 1000),
+            (0, 1000),
+            (0, 0),
+            (300, 300),
+            (300, 700),
+            (700, 700),
+            (700, 300),
+            (300, 300),
+            (0, 0),
+        ]);
+
+        assert!(!donut.contains_point(Point { x: 500, y: 500 }));
+        assert!(donut.contains_point(Point { x: 150, y: 500 }));
+        assert!(!donut.contains_point(Point { x: -500, y: 500 }));
+    }
+
+    #[test]
+    fn test_explode() {
+        let shape = PathShape {
+            start: Point { x: 0, y: 0 },
+            outlines: vec![
+                Outline::Line(vec![SegmentLine {
+                    end: Point { x: 1000, y: 0 },
+                }]),
+                Outline::Bezier(vec![crate::SegmentBezier {
+                    control1: Point { x: 1000, y: 1000 },
+                    control2: Point { x: 0, y: 1000 },
+                    end: Point { x: 0, y: 0 },
+                }]),
+            ],
+        };
+        let parts = shape.explode();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!((parts[0].start.x, parts[0].start.y), (0, 0));
+        assert_eq!((parts[1].start.x, parts[1].start.y), (1000, 0));
+        assert!(matches!(parts[1].outlines.as_slice(), [Outline::Bezier(_)]));
+    }
+
+    #[test]
+    fn test_fit_to_width_mm() {
+        let mut shape = polygon(&[(500, 200), (600, 200), (600, 250), (500, 250)]);
+        shape.fit_to_width_mm(25.4);
+        let (min, max) = shape.bounds();
+        assert_eq!((min.x, min.y), (500, 200));
+        assert_eq!((max.x - min.x, max.y - min.y), (2540, 1270));
+
+        shape.fit_to_height_mm(50.0);
+        let (min, max) = shape.bounds();
+        assert_eq!((min.x, min.y), (500, 200));
+        assert_eq!((max.x - min.x, max.y - min.y), (10000, 5000));
+
+        // Nothing to scale
+        let mut line = polygon(&[(0, 0), (0, 100)]);
+        line.fit_to_width_mm(10.0);
+        assert_eq!(line.bounds().1.y, 100);
+    }
+
+    #[test]
+    fn test_prune_empty_outlines() {
+        let line = |points: &[(i32, i32)]| {
+            Outline::Line(
+                points
+                    .iter()
+                    .map(|&(x, y)| SegmentLine {
+                        end: Point { x, y },
+                    })
+                    .collect(),
+            )
+        };
+        let mut shape = PathShape {
+            start: Point { x: 0, y: 0 },
+            outlines: vec![
+                line(&[(1000, 0)]),
+                line(&[(1002, 1)]),
+                Outline::Bezier(vec![]),
+                line(&[(1000, 1000), (0, 1000), (0, 0)]),
+            ],
+        };
+        assert_eq!(shape.prune_empty_outlines(), 1);
+        assert_eq!(shape.outlines.len(), 3);
+
+        // Snapping collapses the short outline onto the end of the first
+        assert_eq!(shape.snap_to_grid(10), 1);
+        assert_eq!(shape.prune_empty_outlines(), 1);
+        assert_eq!(shape.outlines.len(), 2);
+        assert!(shape.is_closed());
+        assert_eq!(shape.to_polyline(0).len(), 5);
+    }
+
+    #[test]
+    fn test_from_fcm_points() {
+        let corners =
+            [(1000, 1000), (6000, 1000), (6000, 6000), (1000, 6000)].map(|(x, y)| Point { x, y });
+        let square = PathShape::from_fcm_points(&corners, true);
+        assert!(square.is_closed());
+        assert_eq!(square.to_polyline(0).len(), 5);
+
+        let file = crate::FcmFile::from_shapes(
+            vec![square],
+            crate::PathTool::TOOL_CUT,
+            &crate::registration_marks::PageSize::A4,
+        );
+        let parsed = crate::FcmFile::from_bytes(&file.to_bytes().unwrap()).unwrap();
+        let (min, max) = parsed.bounds().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (1000, 1000, 6000, 6000));
+
+        let open = PathShape::from_fcm_points(&corners, false);
+        assert!(!open.is_closed());
+        assert_eq!(open.to_polyline(0).len(), 4);
+        assert!(PathShape::from_fcm_points(&[], true).outlines.is_empty());
+    }
+
+    #[test]
+    fn test_offset() {
+        // Concave L, 20mm across with a 10mm notch
+        let shape = polygon(&[
+            (0, 0),
+            (2000, 0),
+            (2000, 1000),
+            (1000, 1000),
+            (1000, 2000),
+            (0, 2000),
+        ]);
+        let grown = shape.offset(100, JoinStyle::Miter);
+        assert_eq!(grown.len(), 1);
+        let (min, max) = grown[0].bounds();
+        assert_eq!((min.x, min.y, max.x, max.y), (-100, -100, 2100, 2100));
+        assert_eq!(grown[0].signed_area(), 2200.0 * 2200.0 - 1000.0 * 1000.0);
+
+        let shrunk = shape.offset(-100, JoinStyle::Miter);
+        assert_eq!(shrunk.len(), 1);
+        assert_eq!(shrunk[0].signed_area(), 1800.0 * 1800.0 - 1000.0 * 1000.0);
+
+        // Rounded corners cut off part of each square corner
+        let rounded = shape.offset(100, JoinStyle::Round)[0].signed_area();
+        assert!(rounded < 2200.0 * 2200.0 - 1000.0 * 1000.0);
+        assert!(rounded > 2200.0 * 2200.0 - 1000.0 * 1000.0 - 5.0 * 100.0 * 100.0);
+
+        assert!(shape.offset(-600, JoinStyle::Miter).is_empty());
+    }
+
+    #[test]
+    fn test_offset_donut() {
+        // A 20mm square with a 10mm hole, as boolean operations return it
+        let outer = polygon(&[(0, 0), (2000, 0), (2000, 2000), (0, 2000)]);
+        let hole = polygon(&[(500, 500), (1500, 500), (1500, 1500), (500, 1500)]);
+        let donut = boolean::difference(&[outer], &[hole]);
+        assert_eq!(donut.len(), 2);
+        let (outer, hole) = if donut[0].signed_area() > 0.0 {
+            (&donut[0], &donut[1])
+        } else {
+            (&donut[1], &donut[0])
+        };
+        assert!(hole.signed_area() < 0.0);
+
+        let grown = outer.offset(100, JoinStyle::Miter);
+        assert_eq!(grown.len(), 1);
+        let (min, max) = grown[0].bounds();
+        assert_eq!((min.x, min.y, max.x, max.y), (-100, -100, 2100, 2100));
+
+        // The hole gets smaller and keeps winding clockwise
+        let shrunk = hole.offset(100, JoinStyle::Miter);
+        assert_eq!(shrunk.len(), 1);
+        let (min, max) = shrunk[0].bounds();
+        assert_eq!((min.x, min.y, max.x, max.y), (600, 600, 1400, 1400));
+        assert_eq!(shrunk[0].signed_area(), -800.0 * 800.0);
+
+        let widened = hole.offset(-100, JoinStyle::Miter);
+        assert_eq!(widened[0].signed_area(), -1200.0 * 1200.0);
+        assert!(hole.offset(500, JoinStyle::Miter).is_empty());
+    }
+
+    #[test]
+    fn test_line_is_not_convex() {
+        let shape = PathShape {
+            start: Point { x: 0, y: 0 },
+            outlines: vec![Outline::Line(vec![SegmentLine {
+                end: Point { x: 1000, y: 0 },
+            }])],
+        };
+        assert!(!shape.is_convex());
+    }
+}
@@ -8,7 +8,7 @@ use nom::IResult;
 use crate::encode::Encode;
 
 bitflags! {
-    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
     pub struct PathTool: u32 {
             const PATH_OPEN = 0x0001;
             const TOOL_CUT = 0x0002;
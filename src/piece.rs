@@ -10,13 +10,18 @@ use nom::IResult;
 use crate::encode::Encode;
 use crate::path::Path;
 use crate::piece_restrictions::PieceRestrictions;
+use crate::point::Point;
 use crate::util::bool32;
-use crate::{path, piece_restrictions};
+use crate::{geometry, path, piece_restrictions};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Piece {
     pub width: u32,
     pub height: u32,
+    /// Matrix `(a, b, c, d, tx, ty)` placing the piece on the page, with the
+    /// translation in FCM units. The file stores each value as an `f32`,
+    /// which holds every whole number of FCM units up to 2^24 (about 167m),
+    /// far beyond any mat, so translations by whole units are exact.
     pub transform: Option<(f32, f32, f32, f32, f32, f32)>,
     pub expansion_limit_value: u32,
     pub reduction_limit_value: u32,
@@ -25,6 +30,181 @@ pub struct Piece {
     pub paths: Vec<Path>,
 }
 
+impl Piece {
+    /// Create a piece from paths in page coordinates, moving them so the piece
+    /// is centered on its local origin and placed back via the transform
+    pub fn from_paths(mut paths: Vec<Path>) -> Piece {
+        let (min, max) = paths_bounds(&paths).unwrap_or_default();
+        let center_x = (min.x + max.x).div_euclid(2);
+        let center_y = (min.y + max.y).div_euclid(2);
+        for path in &mut paths {
+            path.translate(-center_x, -center_y);
+        }
+
+        Piece {
+            width: (max.x - min.x) as u32,
+            height: (max.y - min.y) as u32,
+            transform: Some((1.0, 0.0, 0.0, 1.0, center_x as f32, center_y as f32)),
+            expansion_limit_value: 0,
+            reduction_limit_value: 0,
+            restriction_flags: PieceRestrictions::empty(),
+            label: String::new(),
+            paths,
+        }
+    }
+
+    /// Width divided by height, below 1 for portrait pieces. Pieces without
+    /// height give infinity, or NaN if they have no width either.
+    pub fn aspect_ratio(&self) -> f64 {
+        self.width as f64 / self.height as f64
+    }
+
+    /// Whether the piece is taller than it is wide
+    pub fn is_portrait(&self) -> bool {
+        self.height > self.width
+    }
+
+    /// Whether the piece is wider than it is tall
+    pub fn is_landscape(&self) -> bool {
+        self.width > self.height
+    }
+
+    /// Minimum and maximum corner of the box containing all paths, in piece coordinates
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        paths_bounds(&self.paths)
+    }
+
+    /// Minimum and maximum corner of the box containing all paths after
+    /// applying the transform, in page coordinates
+    pub fn page_bounds(&self) -> Option<(Point, Point)> {
+        let mut paths = self.paths.clone();
+        for path in &mut paths {
+            path.map_points(|p| self.to_page_point(p));
+        }
+        paths_bounds(&paths)
+    }
+
+    /// Where a point in piece coordinates lands on the page, by applying the
+    /// transform
+    pub fn to_page_point(&self, local: Point) -> Point {
+        let (a, b, c, d, tx, ty) = self.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+        let (x, y) = geometry::to_f64(local);
+        geometry::from_f64(
+            a as f64 * x + c as f64 * y + tx as f64,
+            b as f64 * x + d as f64 * y + ty as f64,
+        )
+    }
+
+    /// The point in piece coordinates that lands on `page` on the page, by
+    /// inverting the transform. Returns `None` if the transform flattens the
+    /// piece and can't be inverted.
+    pub fn from_page_point(&self, page: Point) -> Option<Point> {
+        let (a, b, c, d, tx, ty) = self.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+        let (a, b, c, d) = (a as f64, b as f64, c as f64, d as f64);
+        let det = a * d - b * c;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let (x, y) = geometry::to_f64(page);
+        let (x, y) = (x - tx as f64, y - ty as f64);
+        Some(geometry::from_f64(
+            (d * x - c * y) / det,
+            (a * y - b * x) / det,
+        ))
+    }
+
+    /// Closest approach between the paths of two pieces on the page, in FCM
+    /// units, for checking the spacing of nested pieces. Transforms are
+    /// applied and curves flattened. Pieces whose paths cross, or where one
+    /// lies inside a closed path of the other, are 0 apart. Pieces without
+    /// paths are infinitely far from everything.
+    pub fn min_distance_to(&self, other: &Piece) -> f64 {
+        let (ours, theirs) = (self.page_polylines(), other.page_polylines());
+        let inside = |polylines: &[(Vec<(f64, f64)>, bool)], point: (f64, f64)| {
+            polylines
+                .iter()
+                .any(|(points, closed)| *closed && geometry::point_in_polygon(point, points))
+        };
+        let first_point = |polylines: &[(Vec<(f64, f64)>, bool)]| {
+            polylines
+                .iter()
+                .find_map(|(points, _)| points.first().copied())
+        };
+        if first_point(&ours).is_some_and(|p| inside(&theirs, p))
+            || first_point(&theirs).is_some_and(|p| inside(&ours, p))
+        {
+            return 0.0;
+        }
+
+        let mut distance = f64::INFINITY;
+        for (a, _) in &ours {
+            for (b, _) in &theirs {
+                for (a0, a1) in segments(a) {
+                    for (b0, b1) in segments(b) {
+                        distance = distance.min(geometry::segment_distance(a0, a1, b0, b1));
+                    }
+                }
+            }
+        }
+        distance
+    }
+
+    /// Flattened paths in page coordinates, with whether each is closed
+    fn page_polylines(&self) -> Vec<(Vec<(f64, f64)>, bool)> {
+        let (a, b, c, d, tx, ty) = self.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+        self.paths
+            .iter()
+            .filter_map(|path| path.shape.as_ref())
+            .map(|shape| {
+                let points = shape
+                    .to_polyline(geometry::DEFAULT_TOLERANCE)
+                    .into_iter()
+                    .map(|p| {
+                        let (x, y) = geometry::to_f64(p);
+                        (
+                            a as f64 * x + c as f64 * y + tx as f64,
+                            b as f64 * x + d as f64 * y + ty as f64,
+                        )
+                    })
+                    .collect();
+                (points, shape.is_closed())
+            })
+            .collect()
+    }
+
+    /// Move the piece on the page by changing its transform
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        let (a, b, c, d, tx, ty) = self.transform.unwrap_or((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+        self.transform = Some((a, b, c, d, tx + dx as f32, ty + dy as f32));
+    }
+}
+
+pub(crate) fn paths_bounds(paths: &[Path]) -> Option<(Point, Point)> {
+    paths
+        .iter()
+        .filter_map(Path::bounds)
+        .reduce(|(min_a, max_a), (min_b, max_b)| {
+            (
+                Point {
+                    x: min_a.x.min(min_b.x),
+                    y: min_a.y.min(min_b.y),
+                },
+                Point {
+                    x: max_a.x.max(max_b.x),
+                    y: max_a.y.max(max_b.y),
+                },
+            )
+        })
+}
+
+/// Consecutive point pairs of a polyline, or the point itself for a single point
+fn segments(points: &[(f64, f64)]) -> Vec<((f64, f64), (f64, f64))> {
+    match points {
+        [point] => vec![(*point, *point)],
+        _ => points.windows(2).map(|w| (w[0], w[1])).collect(),
+    }
+}
+
 pub(crate) fn read_piece(input: &[u8]) -> IResult<&[u8], Piece> {
     map(
         tuple((
@@ -119,3 +299,77 @@ impl Encode for Piece {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: i32, y: i32, size: i32) -> Piece {
+        let corners = [(x, y), (x + size, y), (x + size, y + size), (x, y + size)];
+        Piece::from_paths(vec![Path {
+            tool: crate::PathTool::TOOL_CUT,
+            shape: Some(crate::PathShape::from_fcm_points(
+                &corners.map(|(x, y)| Point { x, y }),
+                true,
+            )),
+            rhinestone_diameter: None,
+            rhinestones: vec![],
+        }])
+    }
+
+    #[test]
+    fn test_min_distance_to() {
+        let a = square(0, 0, 1000);
+        let b = square(1500, 0, 1000);
+        assert_eq!(a.min_distance_to(&b), 500.0);
+        assert_eq!(b.min_distance_to(&a), 500.0);
+
+        // Corner to corner
+        let c = square(1300, 1400, 1000);
+        assert_eq!(a.min_distance_to(&c), 500.0);
+
+        // Overlapping, touching, and one inside the other
+        assert_eq!(a.min_distance_to(&square(500, 500, 1000)), 0.0);
+        assert_eq!(a.min_distance_to(&square(1000, 0, 1000)), 0.0);
+        assert_eq!(a.min_distance_to(&square(250, 250, 500)), 0.0);
+
+        // The transform moves the piece
+        let mut moved = b.clone();
+        moved.translate(1000, 0);
+        assert_eq!(a.min_distance_to(&moved), 1500.0);
+
+        assert_eq!(a.min_distance_to(&Piece::from_paths(vec![])), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_orientation() {
+        let mut piece = Piece::from_paths(vec![]);
+        (piece.width, piece.height) = (100, 200);
+        assert_eq!(piece.aspect_ratio(), 0.5);
+        assert!(piece.is_portrait() && !piece.is_landscape());
+
+        (piece.width, piece.height) = (300, 200);
+        assert_eq!(piece.aspect_ratio(), 1.5);
+        assert!(piece.is_landscape() && !piece.is_portrait());
+
+        // Square pieces are neither
+        piece.height = 300;
+        assert!(!piece.is_landscape() && !piece.is_portrait());
+    }
+
+    #[test]
+    fn test_page_point_round_trip() {
+        let mut piece = Piece::from_paths(vec![]);
+        // Rotated by 90° and scaled by 2, centered on (5000, 3000)
+        piece.transform = Some((0.0, 2.0, -2.0, 0.0, 5000.0, 3000.0));
+
+        let local = Point { x: 100, y: -250 };
+        let page = piece.to_page_point(local);
+        assert_eq!((page.x, page.y), (5500, 3200));
+        let back = piece.from_page_point(page).unwrap();
+        assert_eq!((back.x, back.y), (100, -250));
+
+        piece.transform = Some((0.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+        assert!(piece.from_page_point(page).is_none());
+    }
+}
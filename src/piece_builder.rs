@@ -0,0 +1,111 @@
+use crate::path::Path;
+use crate::piece::Piece;
+use crate::piece_restrictions::PieceRestrictions;
+
+/// Builds a [`Piece`] from paths in page coordinates. The size and the
+/// transform placing the centered paths back on the page are computed like
+/// [`Piece::from_paths`].
+///
+/// ```
+/// use fcmlib::{PieceBuilder, PieceRestrictions};
+///
+/// let piece = PieceBuilder::new()
+///     .label("A01")
+///     .restriction(PieceRestrictions::PROHIBITION_OF_SEAM_ALLOWANCE_SETTING)
+///     .build();
+/// assert_eq!(piece.label, "A01");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PieceBuilder {
+    paths: Vec<Path>,
+    label: String,
+    restriction_flags: PieceRestrictions,
+}
+
+impl PieceBuilder {
+    pub fn new() -> PieceBuilder {
+        PieceBuilder {
+            paths: vec![],
+            label: String::new(),
+            restriction_flags: PieceRestrictions::empty(),
+        }
+    }
+
+    /// Set the paths, in page coordinates
+    pub fn paths(mut self, paths: Vec<Path>) -> PieceBuilder {
+        self.paths = paths;
+        self
+    }
+
+    /// Add a path, in page coordinates
+    pub fn path(mut self, path: Path) -> PieceBuilder {
+        self.paths.push(path);
+        self
+    }
+
+    /// Set the label, which Canvas Workspace for the web writes as 3 characters
+    pub fn label<T: Into<String>>(mut self, label: T) -> PieceBuilder {
+        self.label = label.into();
+        self
+    }
+
+    /// Set the edits the machine should refuse on the piece
+    pub fn restriction(mut self, restriction_flags: PieceRestrictions) -> PieceBuilder {
+        self.restriction_flags = restriction_flags;
+        self
+    }
+
+    pub fn build(self) -> Piece {
+        let mut piece = Piece::from_paths(self.paths);
+        piece.label = self.label;
+        piece.restriction_flags = self.restriction_flags;
+        piece
+    }
+}
+
+impl Default for PieceBuilder {
+    fn default() -> Self {
+        PieceBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Outline, PathShape, PathTool, Point, SegmentLine};
+
+    #[test]
+    fn test_build() {
+        let line = |x, y| SegmentLine {
+            end: Point { x, y },
+        };
+        let shape = PathShape {
+            start: Point { x: 1000, y: 2000 },
+            outlines: vec![Outline::Line(vec![
+                line(4001, 2000),
+                line(4001, 3000),
+                line(1000, 2000),
+            ])],
+        };
+        let piece = PieceBuilder::new()
+            .path(Path {
+                tool: PathTool::TOOL_CUT,
+                shape: Some(shape),
+                rhinestone_diameter: None,
+                rhinestones: vec![],
+            })
+            .label("B02")
+            .build();
+
+        // Hand-computed from the bounds (1000, 2000) to (4001, 3000)
+        assert_eq!((piece.width, piece.height), (3001, 1000));
+        assert_eq!(piece.transform, Some((1.0, 0.0, 0.0, 1.0, 2500.0, 2500.0)));
+        let start = piece.paths[0].shape.as_ref().unwrap().start;
+        assert_eq!((start.x, start.y), (-1500, -500));
+        assert_eq!(piece.label, "B02");
+        assert!(piece.restriction_flags.is_empty());
+
+        let (min, max) = piece.page_bounds().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (1000, 2000, 4001, 3000));
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::io::Write;
 
 use bitflags::bitflags;
@@ -8,19 +9,60 @@ use nom::IResult;
 use crate::encode::Encode;
 
 bitflags! {
+    /// Edits the machine and Canvas Workspace allow on a piece.
+    ///
+    /// Sample files only use `PROHIBITION_OF_SEAM_ALLOWANCE_SETTING` or no
+    /// flags, so the other meanings are read from the flag names and haven't
+    /// been checked against the machine. There are no bits for resizing or rotating: how far a piece may
+    /// be scaled is stored in [`Piece::expansion_limit_value`] and
+    /// [`Piece::reduction_limit_value`] instead.
+    ///
+    /// [`Piece::expansion_limit_value`]: crate::Piece::expansion_limit_value
+    /// [`Piece::reduction_limit_value`]: crate::Piece::reduction_limit_value
     #[derive(Debug, Copy, Clone, PartialEq, Eq)]
     pub struct PieceRestrictions: u32 {
+            /// Licensed design from Brother's content library
             const LICENSE_DESIGN = 0x0001;
+            /// The piece has a seam allowance
             const SEAM_ALLOWANCE = 0x0002;
+            /// The seam allowance can't be changed
             const PROHIBITION_OF_SEAM_ALLOWANCE_SETTING = 0x0004;
+            /// Bit 0x0020, which the format gives two names. What it does is
+            /// unknown: the names suggest either a locked aspect ratio or auto
+            /// layout by the exact shape rather than the bounding box.
             const NO_ASPECT_RATIO_CHANGE_PROHIBITED = 0x0020;
+            /// The same bit as `NO_ASPECT_RATIO_CHANGE_PROHIBITED`
             const JUDGE_BY_USING_PERFECT_MASK_AT_AUTO_LAYOUT = 0x0020;
+            /// Test pattern used to check the blade
             const TEST_PATTERN = 0x0040;
+            /// The piece can't be edited
             const PROHIBITION_OF_EDIT = 0x0080;
+            /// The tool of the paths can't be changed
             const PROHIBITION_OF_TOOL = 0x0100;
             const _ = !0;
     }
 }
+
+impl PieceRestrictions {
+    /// Shorter name for `PROHIBITION_OF_EDIT`
+    pub const NO_EDIT: PieceRestrictions = PieceRestrictions::PROHIBITION_OF_EDIT;
+    /// Shorter name for `PROHIBITION_OF_TOOL`
+    pub const NO_TOOL_CHANGE: PieceRestrictions = PieceRestrictions::PROHIBITION_OF_TOOL;
+    /// Shorter name for `PROHIBITION_OF_SEAM_ALLOWANCE_SETTING`
+    pub const NO_SEAM_ALLOWANCE_CHANGE: PieceRestrictions =
+        PieceRestrictions::PROHIBITION_OF_SEAM_ALLOWANCE_SETTING;
+}
+
+impl Display for PieceRestrictions {
+    /// Names of the set flags joined by " | ", unnamed bits in hex, or "none"
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+        bitflags::parser::to_writer(self, f)
+    }
+}
+
 pub(crate) fn read_piece_restrictions(input: &[u8]) -> IResult<&[u8], PieceRestrictions> {
     map_opt(le_u32, PieceRestrictions::from_bits)(input)
 }
@@ -31,3 +73,38 @@ impl Encode for PieceRestrictions {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for flag in PieceRestrictions::all().iter() {
+            let bytes = flag.encode_to_vec().unwrap();
+            let (_, parsed) = read_piece_restrictions(&bytes).unwrap();
+            assert_eq!(parsed, flag);
+        }
+
+        let restrictions = PieceRestrictions::NO_EDIT | PieceRestrictions::NO_TOOL_CHANGE;
+        let bytes = restrictions.encode_to_vec().unwrap();
+        let (_, parsed) = read_piece_restrictions(&bytes).unwrap();
+        assert!(parsed.contains(PieceRestrictions::PROHIBITION_OF_EDIT));
+        assert!(!parsed.contains(PieceRestrictions::TEST_PATTERN));
+
+        // One bit with two names
+        assert_eq!(
+            PieceRestrictions::NO_ASPECT_RATIO_CHANGE_PROHIBITED,
+            PieceRestrictions::JUDGE_BY_USING_PERFECT_MASK_AT_AUTO_LAYOUT
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(PieceRestrictions::empty().to_string(), "none");
+        assert_eq!(
+            (PieceRestrictions::SEAM_ALLOWANCE | PieceRestrictions::NO_EDIT).to_string(),
+            "SEAM_ALLOWANCE | PROHIBITION_OF_EDIT"
+        );
+    }
+}
@@ -16,6 +16,31 @@ pub struct PieceTable {
     pub pieces: Vec<(u16, Piece)>,
 }
 
+impl PieceTable {
+    /// Pieces with their ids, in file order
+    pub fn iter(&self) -> std::slice::Iter<'_, (u16, Piece)> {
+        self.pieces.iter()
+    }
+}
+
+impl IntoIterator for PieceTable {
+    type Item = (u16, Piece);
+    type IntoIter = std::vec::IntoIter<(u16, Piece)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pieces.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PieceTable {
+    type Item = &'a (u16, Piece);
+    type IntoIter = std::slice::Iter<'a, (u16, Piece)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pieces.iter()
+    }
+}
+
 pub fn read_piece_table(input: &[u8]) -> IResult<&[u8], PieceTable> {
     flat_map(
         tuple((
@@ -59,3 +84,29 @@ impl Encode for PieceTable {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterate() {
+        let table = PieceTable {
+            pieces: vec![
+                (3, Piece::from_paths(vec![])),
+                (7, Piece::from_paths(vec![])),
+            ],
+        };
+
+        let mut ids = vec![];
+        for (id, piece) in &table {
+            assert!(piece.paths.is_empty());
+            ids.push(*id);
+        }
+        assert_eq!(ids, vec![3, 7]);
+        assert_eq!(table.iter().count(), 2);
+
+        let owned: Vec<u16> = table.into_iter().map(|(id, _)| id).collect();
+        assert_eq!(owned, vec![3, 7]);
+    }
+}
@@ -29,6 +29,7 @@
 //! - Y inset: ~14.0mm from top/bottom edges (13.98mm exactly)
 //! - Marks are centered at these inset positions
 
+use crate::svg_path::SvgConfig;
 use crate::Point;
 
 /// Registration mark dimensions (all values in mm, extracted from Illustrator)
@@ -37,6 +38,9 @@ pub mod dimensions {
     pub const BG_WIDTH_MM: f64 = 16.0;
     /// White background rectangle height (51pt = 18.0mm)
     pub const BG_HEIGHT_MM: f64 = 18.0;
+    /// Distance from the mark center up to the top of the background (28.3pt = 10.0mm).
+    /// The background reaches further above the center than below to fit the bar.
+    pub const BG_TOP_MM: f64 = 10.0;
 
     /// Center dot radius (2.8pt = 0.99mm)
     pub const CENTER_DOT_RADIUS_MM: f64 = 0.99;
@@ -86,6 +90,22 @@ impl PageSize {
     pub fn to_fcm_units(&self) -> (u32, u32) {
         ((self.width_mm * 100.0) as u32, (self.height_mm * 100.0) as u32)
     }
+
+    /// Minimum and maximum corner of the area the machine can reach, in FCM
+    /// units, leaving `margin_fcm` on each side for the rollers and clamps
+    pub fn cuttable_area(&self, margin_fcm: i32) -> (Point, Point) {
+        let (width, height) = self.to_fcm_units();
+        (
+            Point {
+                x: margin_fcm,
+                y: margin_fcm,
+            },
+            Point {
+                x: width as i32 - margin_fcm,
+                y: height as i32 - margin_fcm,
+            },
+        )
+    }
 }
 
 /// Position of a single registration mark
@@ -117,30 +137,133 @@ impl MarkPosition {
 pub fn calculate_mark_positions(page: &PageSize) -> [MarkPosition; 4] {
     use dimensions::*;
 
+    corner_positions(page, X_INSET_MM, Y_INSET_MM)
+}
+
+/// Calculate the 4 registration mark positions inset by a fraction of the
+/// page size, e.g. `0.08` for 8% of the width/height from each edge
+pub fn calculate_mark_positions_percent(
+    page: &PageSize,
+    x_frac: f64,
+    y_frac: f64,
+) -> [MarkPosition; 4] {
+    corner_positions(page, page.width_mm * x_frac, page.height_mm * y_frac)
+}
+
+/// Mark positions `x_inset` and `y_inset` mm in from each corner of the page
+fn corner_positions(page: &PageSize, x_inset: f64, y_inset: f64) -> [MarkPosition; 4] {
     [
         // Top-left
         MarkPosition {
-            x_mm: X_INSET_MM,
-            y_mm: Y_INSET_MM,
+            x_mm: x_inset,
+            y_mm: y_inset,
         },
         // Top-right
         MarkPosition {
-            x_mm: page.width_mm - X_INSET_MM,
-            y_mm: Y_INSET_MM,
+            x_mm: page.width_mm - x_inset,
+            y_mm: y_inset,
         },
         // Bottom-right
         MarkPosition {
-            x_mm: page.width_mm - X_INSET_MM,
-            y_mm: page.height_mm - Y_INSET_MM,
+            x_mm: page.width_mm - x_inset,
+            y_mm: page.height_mm - y_inset,
         },
         // Bottom-left
         MarkPosition {
-            x_mm: X_INSET_MM,
-            y_mm: page.height_mm - Y_INSET_MM,
+            x_mm: x_inset,
+            y_mm: page.height_mm - y_inset,
         },
     ]
 }
 
+/// Minimum and maximum corner of the area between the registration marks, in
+/// FCM units. Artwork inside it doesn't overlap any mark or its background.
+pub fn safe_artwork_rect(page: &PageSize) -> (Point, Point) {
+    use dimensions::*;
+
+    let x_margin = X_INSET_MM + BG_WIDTH_MM / 2.0;
+    let top_margin = Y_INSET_MM + (BG_HEIGHT_MM - BG_TOP_MM);
+    let bottom_margin = Y_INSET_MM + BG_TOP_MM;
+    (
+        MarkPosition {
+            x_mm: x_margin,
+            y_mm: top_margin,
+        }
+        .to_fcm_point(),
+        MarkPosition {
+            x_mm: page.width_mm - x_margin,
+            y_mm: page.height_mm - bottom_margin,
+        }
+        .to_fcm_point(),
+    )
+}
+
+/// Scale and position of artwork centered on a page. The printed SVG and the
+/// cut file both take their placement from this, so the cut lines land on
+/// the printed artwork.
+#[derive(Debug, Clone, Copy)]
+pub struct ArtworkFit {
+    /// Top left corner of the artwork in its own units, e.g. the viewBox origin
+    pub origin: (f64, f64),
+    /// Size of one artwork unit on the page in mm
+    pub mm_per_unit: f64,
+    /// X position of the artwork's top left corner on the page in mm
+    pub offset_x_mm: f64,
+    /// Y position of the artwork's top left corner on the page in mm
+    pub offset_y_mm: f64,
+}
+
+impl ArtworkFit {
+    /// SVG `transform` attribute value placing the artwork on a page whose
+    /// user units are `units_per_mm`, e.g. `72.0 / 25.4` for points
+    pub fn svg_transform(&self, units_per_mm: f64) -> String {
+        format!(
+            "translate({} {}) scale({}) translate({} {})",
+            self.offset_x_mm * units_per_mm,
+            self.offset_y_mm * units_per_mm,
+            self.mm_per_unit * units_per_mm,
+            -self.origin.0,
+            -self.origin.1
+        )
+    }
+
+    /// Config converting artwork coordinates to FCM page coordinates
+    pub fn svg_config(&self) -> SvgConfig {
+        SvgConfig {
+            dpi: 25.4 / self.mm_per_unit,
+            scale: 1.0,
+            offset_x_mm: self.offset_x_mm - self.origin.0 * self.mm_per_unit,
+            offset_y_mm: self.offset_y_mm - self.origin.1 * self.mm_per_unit,
+            ..Default::default()
+        }
+    }
+}
+
+/// Fit artwork covering `viewbox` (min x, min y, width, height in artwork
+/// units) centered on the page, as large as it gets without reaching into
+/// `margin_mm` on any side. The aspect ratio is kept. Returns `None` if the
+/// viewBox or the space inside the margins has no area.
+pub fn fit_to_page(
+    page: &PageSize,
+    viewbox: (f64, f64, f64, f64),
+    margin_mm: f64,
+) -> Option<ArtworkFit> {
+    let (min_x, min_y, width, height) = viewbox;
+    let available_width = page.width_mm - 2.0 * margin_mm;
+    let available_height = page.height_mm - 2.0 * margin_mm;
+    if width <= 0.0 || height <= 0.0 || available_width <= 0.0 || available_height <= 0.0 {
+        return None;
+    }
+
+    let mm_per_unit = (available_width / width).min(available_height / height);
+    Some(ArtworkFit {
+        origin: (min_x, min_y),
+        mm_per_unit,
+        offset_x_mm: (page.width_mm - width * mm_per_unit) / 2.0,
+        offset_y_mm: (page.height_mm - height * mm_per_unit) / 2.0,
+    })
+}
+
 /// Get FCM AlignmentData marks for a page size
 pub fn get_fcm_alignment_marks(page: &PageSize) -> Vec<Point> {
     calculate_mark_positions(page)
@@ -150,43 +273,161 @@ pub fn get_fcm_alignment_marks(page: &PageSize) -> Vec<Point> {
 }
 
 /// Exact registration mark SVG from Adobe Illustrator ScanNCut plugin.
-/// This is R1 verbatim - centered at (34.03858, 39.63858) in the original.
-const MARK_TEMPLATE: &str = r##"<rect x="11.33858" y="11.33858" width="45.4" height="51" fill="#fff"/>
-      <circle cx="34.03858" cy="39.63858" r="2.8" fill="#070404"/>
-      <path d="M34.03858,48.13858c-4.7,0-8.5-3.8-8.5-8.5s3.8-8.5,8.5-8.5,8.5,3.8,8.5,8.5-3.8,8.5-8.5,8.5ZM34.03858,34.03858c-3.1,0-5.7,2.5-5.7,5.7,0,3.1,2.5,5.7,5.7,5.7s5.7-2.5,5.7-5.7c-.1-3.2-2.6-5.7-5.7-5.7Z" fill="#070404"/>
-      <path d="M34.03858,53.83858c-7.8,0-14.2-6.4-14.2-14.2s6.4-14.2,14.2-14.2,14.2,6.4,14.2,14.2c0,7.9-6.4,14.2-14.2,14.2ZM34.03858,28.33858c-6.3,0-11.3,5.1-11.3,11.3s5.1,11.3,11.3,11.3,11.3-5,11.3-11.3-5.1-11.3-11.3-11.3Z" fill="#070404"/>
-      <rect x="19.83858" y="19.83858" width="28.3" height="2.8" fill="#070404"/>"##;
+/// This is R1 verbatim - centered at (34.03858, 39.63858) in the original,
+/// with the colors replaced by `{fill}` and `{background}` placeholders.
+const MARK_TEMPLATE: &str = r##"<rect x="11.33858" y="11.33858" width="45.4" height="51" fill="{background}"/>
+      <circle cx="34.03858" cy="39.63858" r="2.8" fill="{fill}"/>
+      <path d="M34.03858,48.13858c-4.7,0-8.5-3.8-8.5-8.5s3.8-8.5,8.5-8.5,8.5,3.8,8.5,8.5-3.8,8.5-8.5,8.5ZM34.03858,34.03858c-3.1,0-5.7,2.5-5.7,5.7,0,3.1,2.5,5.7,5.7,5.7s5.7-2.5,5.7-5.7c-.1-3.2-2.6-5.7-5.7-5.7Z" fill="{fill}"/>
+      <path d="M34.03858,53.83858c-7.8,0-14.2-6.4-14.2-14.2s6.4-14.2,14.2-14.2,14.2,6.4,14.2,14.2c0,7.9-6.4,14.2-14.2,14.2ZM34.03858,28.33858c-6.3,0-11.3,5.1-11.3,11.3s5.1,11.3,11.3,11.3,11.3-5,11.3-11.3-5.1-11.3-11.3-11.3Z" fill="{fill}"/>
+      <rect x="19.83858" y="19.83858" width="28.3" height="2.8" fill="{fill}"/>"##;
 
 /// The center point of the template mark (where it was in the original Illustrator export)
 const TEMPLATE_CENTER_X: f64 = 34.03858;
 const TEMPLATE_CENTER_Y: f64 = 39.63858;
 
+/// Colors of a registration mark, as SVG color values
+#[derive(Debug, Clone)]
+pub struct MarkColors {
+    /// Color of the rings, dot and bar
+    pub fill: String,
+    /// Color of the rectangle behind the mark
+    pub background: String,
+}
+
+impl Default for MarkColors {
+    /// The near-black on white used by the Illustrator plugin
+    fn default() -> Self {
+        Self {
+            fill: String::from("#070404"),
+            background: String::from("#fff"),
+        }
+    }
+}
+
+/// Style of the printed registration marks. Only the printed art differs, the
+/// FCM alignment points are the same for every style.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkType {
+    /// The bullseye and bar from the Illustrator plugin
+    BrotherBullseye,
+    /// A plus sign, `size_mm` across
+    Crosshair { size_mm: f64 },
+    /// A corner with arms `size_mm` long, pointing into the page
+    LShaped { size_mm: f64 },
+}
+
+/// Stroke width of the crosshair and L-shaped marks
+const LINE_MARK_STROKE_MM: f64 = 0.5;
+
 /// Generate a single registration mark as SVG at the given center position.
 /// Uses the exact mark from Adobe Illustrator, positioned via transform.
 pub fn generate_mark_svg(cx: f64, cy: f64, id: &str) -> String {
+    generate_mark_svg_with_colors(cx, cy, id, &MarkColors::default())
+}
+
+/// Generate a single registration mark as SVG at the given center position,
+/// with custom colors for printers or paper the default doesn't suit
+pub fn generate_mark_svg_with_colors(cx: f64, cy: f64, id: &str, colors: &MarkColors) -> String {
     let tx = cx - TEMPLATE_CENTER_X;
     let ty = cy - TEMPLATE_CENTER_Y;
+    let mark = MARK_TEMPLATE
+        .replace("{fill}", &colors.fill)
+        .replace("{background}", &colors.background);
 
     format!(
         "  <g id=\"{}\" transform=\"translate({:.5}, {:.5})\">\n      {}\n  </g>",
-        id, tx, ty, MARK_TEMPLATE
+        id, tx, ty, mark
     )
 }
 
+/// Generate a single registration mark of the given type and colors as SVG,
+/// centered at `(cx, cy)` in points. L-shaped marks are drawn as a top-left
+/// corner. Crosshair and L-shaped marks are drawn in the fill color only.
+pub fn generate_mark_svg_with_type(
+    cx: f64,
+    cy: f64,
+    id: &str,
+    mark_type: MarkType,
+    colors: &MarkColors,
+) -> String {
+    mark_svg(cx, cy, id, mark_type, colors, (1.0, 1.0))
+}
+
+/// `direction` is the way the arms of an L-shaped mark point, as signs of x and y
+fn mark_svg(
+    cx: f64,
+    cy: f64,
+    id: &str,
+    mark_type: MarkType,
+    colors: &MarkColors,
+    direction: (f64, f64),
+) -> String {
+    let mm_to_pt = 72.0 / 25.4;
+    let line = |x1: f64, y1: f64, x2: f64, y2: f64| {
+        format!(
+            "<line x1=\"{:.5}\" y1=\"{:.5}\" x2=\"{:.5}\" y2=\"{:.5}\" stroke=\"{}\" \
+             stroke-width=\"{:.5}\" stroke-linecap=\"square\"/>",
+            x1,
+            y1,
+            x2,
+            y2,
+            colors.fill,
+            LINE_MARK_STROKE_MM * mm_to_pt
+        )
+    };
+
+    let lines = match mark_type {
+        MarkType::BrotherBullseye => return generate_mark_svg_with_colors(cx, cy, id, colors),
+        MarkType::Crosshair { size_mm } => {
+            let half = size_mm * mm_to_pt / 2.0;
+            [
+                line(cx - half, cy, cx + half, cy),
+                line(cx, cy - half, cx, cy + half),
+            ]
+        }
+        MarkType::LShaped { size_mm } => {
+            let arm = size_mm * mm_to_pt;
+            [
+                line(cx, cy, cx + arm * direction.0, cy),
+                line(cx, cy, cx, cy + arm * direction.1),
+            ]
+        }
+    };
+
+    format!("  <g id=\"{}\">\n      {}\n  </g>", id, lines.join("\n      "))
+}
+
 /// Generate complete SVG with all 4 registration marks for a page
 pub fn generate_registration_marks_svg(page: &PageSize) -> String {
+    generate_registration_marks_svg_with_type(
+        page,
+        MarkType::BrotherBullseye,
+        &MarkColors::default(),
+    )
+}
+
+/// Generate complete SVG with all 4 registration marks of the given type and
+/// colors for a page
+pub fn generate_registration_marks_svg_with_type(
+    page: &PageSize,
+    mark_type: MarkType,
+    colors: &MarkColors,
+) -> String {
     let mm_to_pt = 72.0 / 25.4;
     let width_pt = page.width_mm * mm_to_pt;
     let height_pt = page.height_mm * mm_to_pt;
 
     let positions = calculate_mark_positions(page);
+    // Top-left, top-right, bottom-right, bottom-left
+    let directions = [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)];
 
     let marks: Vec<String> = positions
         .iter()
+        .zip(directions)
         .enumerate()
-        .map(|(i, pos)| {
+        .map(|(i, (pos, direction))| {
             let (x, y) = pos.to_svg_coords(72.0);
-            generate_mark_svg(x, y, &format!("R{}", i + 1))
+            mark_svg(x, y, &format!("R{}", i + 1), mark_type, colors, direction)
         })
         .collect();
 
@@ -241,6 +482,161 @@ mod tests {
         assert!((positions[2].y_mm - 265.42).abs() < 0.01);
     }
 
+    #[test]
+    fn test_mark_colors() {
+        let default = generate_mark_svg(50.0, 50.0, "R1");
+        assert!(default.contains("fill=\"#070404\""));
+        assert!(default.contains("fill=\"#fff\""));
+
+        let colors = MarkColors {
+            fill: String::from("#000000"),
+            background: String::from("#ffffe0"),
+        };
+        let custom = generate_mark_svg_with_colors(50.0, 50.0, "R1", &colors);
+        assert!(custom.contains("fill=\"#000000\""));
+        assert!(custom.contains("fill=\"#ffffe0\""));
+        assert!(!custom.contains("#070404"));
+        assert!(!custom.contains('{'));
+    }
+
+    #[test]
+    fn test_crosshair_marks() {
+        let svg = generate_registration_marks_svg_with_type(
+            &PageSize::LETTER,
+            MarkType::Crosshair { size_mm: 10.0 },
+            &MarkColors::default(),
+        );
+        assert_eq!(svg.matches("<line").count(), 8);
+        assert!(!svg.contains("<circle"));
+        assert!(svg.contains("id=\"R4\""));
+
+        // 10mm is 28.34646pt across, centered on x = 34pt
+        let crosshair = MarkType::Crosshair { size_mm: 10.0 };
+        let mark = generate_mark_svg_with_type(34.0, 40.0, "R1", crosshair, &MarkColors::default());
+        assert!(mark.contains("x1=\"19.82677\" y1=\"40.00000\" x2=\"48.17323\""));
+
+        let colors = MarkColors {
+            fill: String::from("#000000"),
+            background: String::from("#ffffe0"),
+        };
+        let mark = generate_mark_svg_with_type(34.0, 40.0, "R1", crosshair, &colors);
+        assert!(mark.contains("stroke=\"#000000\""));
+        assert!(!mark.contains("#070404"));
+        let svg = generate_registration_marks_svg_with_type(
+            &PageSize::LETTER,
+            MarkType::BrotherBullseye,
+            &colors,
+        );
+        assert!(svg.contains("fill=\"#ffffe0\""));
+    }
+
+    #[test]
+    fn test_l_shaped_marks_point_inwards() {
+        let svg = generate_registration_marks_svg_with_type(
+            &PageSize::new(100.0, 100.0),
+            MarkType::LShaped { size_mm: 25.4 },
+            &MarkColors::default(),
+        );
+        assert_eq!(svg.matches("<line").count(), 8);
+        // The top-right mark at x = 88mm = 249.44882pt has its arm going left
+        assert!(svg.contains("x1=\"249.44882\" y1=\"39.62835\" x2=\"177.44882\""));
+        assert_eq!(
+            generate_registration_marks_svg_with_type(
+                &PageSize::LETTER,
+                MarkType::BrotherBullseye,
+                &MarkColors::default(),
+            ),
+            generate_registration_marks_svg(&PageSize::LETTER)
+        );
+    }
+
+    #[test]
+    fn test_safe_artwork_rect() {
+        use dimensions::*;
+
+        let (min, max) = safe_artwork_rect(&PageSize::LETTER);
+        assert_eq!((min.x, min.y, max.x, max.y), (2000, 2198, 19590, 25542));
+
+        // No mark background reaches into the rectangle
+        for mark in calculate_mark_positions(&PageSize::LETTER) {
+            let center = mark.to_fcm_point();
+            let half_width = (BG_WIDTH_MM * 50.0) as i32;
+            let bg_min = Point {
+                x: center.x - half_width,
+                y: center.y - (BG_TOP_MM * 100.0) as i32,
+            };
+            let bg_max = Point {
+                x: center.x + half_width,
+                y: center.y + ((BG_HEIGHT_MM - BG_TOP_MM) * 100.0) as i32,
+            };
+            let overlaps_x = bg_min.x < max.x && bg_max.x > min.x;
+            let overlaps_y = bg_min.y < max.y && bg_max.y > min.y;
+            assert!(!(overlaps_x && overlaps_y));
+        }
+    }
+
+    #[test]
+    fn test_fit_to_page() {
+        let page = PageSize::new(127.0, 177.8);
+        let fit = fit_to_page(&page, (10.0, 20.0, 200.0, 100.0), 25.0).unwrap();
+        assert!((fit.mm_per_unit - 0.385).abs() < 1e-9);
+        assert!((fit.offset_x_mm - 25.0).abs() < 1e-9);
+        assert!((fit.offset_y_mm - (177.8 - 38.5) / 2.0).abs() < 1e-9);
+
+        assert!(fit_to_page(&page, (0.0, 0.0, 0.0, 100.0), 25.0).is_none());
+        assert!(fit_to_page(&page, (0.0, 0.0, 100.0, 100.0), 70.0).is_none());
+    }
+
+    #[test]
+    fn test_print_and_cut_placement_match() {
+        use crate::svg_document::parse_document;
+        use crate::svg_path::SvgPathParser;
+
+        // Artwork with its viewBox away from the origin on a 5x7" page
+        let page = PageSize::new(127.0, 177.8);
+        let d = "M 30,40 L 230,40 L 230,90 C 180,140 80,140 30,90 Z";
+        let fit = fit_to_page(&page, (30.0, 40.0, 200.0, 100.0), 25.0).unwrap();
+
+        // The printed page is in points, the artwork placed by the transform
+        let pt_per_mm = 72.0 / 25.4;
+        let print_svg = format!(
+            r#"<svg><g transform="{}"><path d="{}"/></g></svg>"#,
+            fit.svg_transform(pt_per_mm),
+            d
+        );
+        let points_config = SvgConfig { dpi: 72.0, ..Default::default() };
+        let printed = parse_document(&print_svg, &points_config).unwrap().shapes;
+        let cut = SvgPathParser::new(fit.svg_config()).parse(d).unwrap();
+
+        assert_eq!(printed.len(), 1);
+        assert_eq!(cut.len(), 1);
+        let points = |shape: &crate::PathShape| -> Vec<(i32, i32)> {
+            shape.to_polyline(0).iter().map(|p| (p.x, p.y)).collect()
+        };
+        let (printed, cut) = (points(&printed[0]), points(&cut[0]));
+        assert_eq!(printed.len(), cut.len());
+        for (a, b) in printed.iter().zip(&cut) {
+            assert!((a.0 - b.0).abs() <= 1 && (a.1 - b.1).abs() <= 1, "{:?} != {:?}", a, b);
+        }
+
+        // Centered on the page, 25mm from the sides
+        let (min, max) = SvgPathParser::new(fit.svg_config()).parse(d).unwrap()[0].bounds();
+        assert_eq!((min.x, max.x), (2500, 10200));
+        assert_eq!(min.y + max.y, 17780);
+    }
+
+    #[test]
+    fn test_percent_positions() {
+        let page = PageSize::new(200.0, 200.0);
+        let positions = calculate_mark_positions_percent(&page, 0.1, 0.1);
+        let expected = [(20.0, 20.0), (180.0, 20.0), (180.0, 180.0), (20.0, 180.0)];
+
+        for (position, (x, y)) in positions.iter().zip(expected) {
+            assert!((position.x_mm - x).abs() < 0.01);
+            assert!((position.y_mm - y).abs() < 0.01);
+        }
+    }
+
     #[test]
     fn test_fcm_points() {
         let marks = get_fcm_alignment_marks(&PageSize::LETTER);
@@ -1,4 +1,5 @@
 use crate::encode::Encode;
+use crate::geometry::{from_f64, to_f64};
 use crate::point::{read_point, Point};
 use nom::combinator::map;
 use nom::sequence::tuple;
@@ -11,6 +12,44 @@ pub struct SegmentBezier {
     pub end: Point,
 }
 
+impl SegmentBezier {
+    /// Split the curve starting at `from` at parameter `t` using de Casteljau's
+    /// algorithm, returning both halves and the point where they meet. The
+    /// second half starts at the returned point.
+    pub fn split_at(&self, from: Point, t: f64) -> (SegmentBezier, SegmentBezier, Point) {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: (f64, f64), b: (f64, f64)| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+        let point = |p: (f64, f64)| from_f64(p.0, p.1);
+
+        let (p0, p1, p2, p3) = (
+            to_f64(from),
+            to_f64(self.control1),
+            to_f64(self.control2),
+            to_f64(self.end),
+        );
+        let p01 = lerp(p0, p1);
+        let p12 = lerp(p1, p2);
+        let p23 = lerp(p2, p3);
+        let p012 = lerp(p01, p12);
+        let p123 = lerp(p12, p23);
+        let split = point(lerp(p012, p123));
+
+        (
+            SegmentBezier {
+                control1: point(p01),
+                control2: point(p012),
+                end: split,
+            },
+            SegmentBezier {
+                control1: point(p123),
+                control2: point(p23),
+                end: self.end,
+            },
+            split,
+        )
+    }
+}
+
 pub(crate) fn read_segment_bezier(input: &[u8]) -> IResult<&[u8], SegmentBezier> {
     map(
         tuple((read_point, read_point, read_point)),
@@ -30,3 +69,34 @@ impl Encode for SegmentBezier {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xy(p: Point) -> (i32, i32) {
+        (p.x, p.y)
+    }
+
+    #[test]
+    fn test_split_symmetric() {
+        let segment = SegmentBezier {
+            control1: Point { x: 0, y: 1000 },
+            control2: Point { x: 2000, y: 1000 },
+            end: Point { x: 2000, y: 0 },
+        };
+        let (first, second, split) = segment.split_at(Point { x: 0, y: 0 }, 0.5);
+
+        assert_eq!(xy(split), (1000, 750));
+        assert_eq!(xy(first.end), xy(split));
+        assert_eq!(xy(first.control1), (0, 500));
+        assert_eq!(xy(first.control2), (500, 750));
+        assert_eq!(xy(second.control1), (1500, 750));
+        assert_eq!(xy(second.control2), (2000, 500));
+        assert_eq!(xy(second.end), (2000, 0));
+
+        // Mirror images across x = 1000
+        assert_eq!(first.control1.x, 2000 - second.control2.x);
+        assert_eq!(first.control2.x, 2000 - second.control1.x);
+    }
+}
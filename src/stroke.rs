@@ -0,0 +1,288 @@
+use std::f64::consts::PI;
+
+use crate::geometry::{self, from_f64, length, to_f64};
+use crate::{PathShape, Point};
+
+/// How the ends of an open stroke are finished
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CapStyle {
+    /// Ends flush with the end points
+    Butt,
+    /// Extends past the end points by half the stroke width
+    Square,
+    /// Semicircle around the end points
+    Round,
+}
+
+/// How the outer side of a corner is finished
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JoinStyle {
+    /// Sharp corner, falling back to bevel past a miter limit of 4
+    Miter,
+    /// Arc around the corner
+    Round,
+    /// Straight line across the corner
+    Bevel,
+}
+
+const MITER_LIMIT: f64 = 4.0;
+const ARC_STEP: f64 = PI / 16.0;
+
+type Vec2 = (f64, f64);
+
+/// Expand a centerline polyline into the closed outline of a stroke with the given width
+pub(crate) fn stroke_polyline(
+    points: &[Point],
+    width: i32,
+    cap: CapStyle,
+    join: JoinStyle,
+) -> PathShape {
+    let mut points: Vec<Vec2> = points.iter().copied().map(to_f64).collect();
+    points.dedup();
+    if points.len() < 2 {
+        let p = points.first().copied().unwrap_or((0.0, 0.0));
+        points = vec![p, p];
+    }
+
+    let half = width.abs() as f64 / 2.0;
+    let directions: Vec<Vec2> = points
+        .windows(2)
+        .map(|w| normalize((w[1].0 - w[0].0, w[1].1 - w[0].1)))
+        .collect();
+
+    let mut result: Vec<Vec2> = Vec::new();
+
+    // Left side, forwards
+    result.push(offset(points[0], directions[0], half));
+    for i in 1..points.len() - 1 {
+        add_join(
+            &mut result,
+            points[i],
+            directions[i - 1],
+            directions[i],
+            half,
+            join,
+        );
+    }
+    let last = points.len() - 1;
+    let end_direction = directions[last - 1];
+    result.push(offset(points[last], end_direction, half));
+
+    // End cap
+    add_cap(&mut result, points[last], end_direction, half, cap);
+
+    // Right side, backwards
+    result.push(offset(points[last], end_direction, -half));
+    for i in (1..points.len() - 1).rev() {
+        add_join(
+            &mut result,
+            points[i],
+            neg(directions[i]),
+            neg(directions[i - 1]),
+            half,
+            join,
+        );
+    }
+    result.push(offset(points[0], directions[0], -half));
+
+    // Start cap
+    add_cap(&mut result, points[0], neg(directions[0]), half, cap);
+
+    let mut rounded: Vec<Point> = result.into_iter().map(|(x, y)| from_f64(x, y)).collect();
+    rounded.dedup_by(|a, b| a.x == b.x && a.y == b.y);
+
+    geometry::polygon_to_shape(&rounded)
+}
+
+/// Add the offset geometry for the left side of a corner at `vertex` turning from `incoming` to `outgoing`
+fn add_join(
+    result: &mut Vec<Vec2>,
+    vertex: Vec2,
+    incoming: Vec2,
+    outgoing: Vec2,
+    half: f64,
+    join: JoinStyle,
+) {
+    let a = offset(vertex, incoming, half);
+    let b = offset(vertex, outgoing, half);
+    let turn = cross(incoming, outgoing);
+
+    if turn.abs() < 1e-9 {
+        if dot(incoming, outgoing) < 0.0 {
+            // Full reversal, treat like a cap
+            result.push(a);
+            add_cap(
+                result,
+                vertex,
+                incoming,
+                half,
+                match join {
+                    JoinStyle::Round => CapStyle::Round,
+                    _ => CapStyle::Butt,
+                },
+            );
+            result.push(b);
+        } else {
+            result.push(a);
+        }
+        return;
+    }
+
+    // Left turns put the left side on the inside of the corner
+    let miter = intersect(a, incoming, b, outgoing);
+    if turn > 0.0 {
+        result.push(miter.unwrap_or(a));
+        return;
+    }
+
+    match join {
+        JoinStyle::Miter => match miter {
+            Some(m) if length(m.0 - vertex.0, m.1 - vertex.1) <= MITER_LIMIT * half => {
+                result.push(m)
+            }
+            _ => {
+                result.push(a);
+                result.push(b);
+            }
+        },
+        JoinStyle::Bevel => {
+            result.push(a);
+            result.push(b);
+        }
+        JoinStyle::Round => {
+            let from = (a.1 - vertex.1).atan2(a.0 - vertex.0);
+            let mut sweep = (b.1 - vertex.1).atan2(b.0 - vertex.0) - from;
+            while sweep > 0.0 {
+                sweep -= 2.0 * PI;
+            }
+            add_arc(result, vertex, half, from, sweep);
+        }
+    }
+}
+
+/// Add the cap around `end`, travelling from the left offset to the right offset of `direction`
+fn add_cap(result: &mut Vec<Vec2>, end: Vec2, direction: Vec2, half: f64, cap: CapStyle) {
+    match cap {
+        CapStyle::Butt => {}
+        CapStyle::Square => {
+            let left = offset(end, direction, half);
+            let right = offset(end, direction, -half);
+            result.push((left.0 + direction.0 * half, left.1 + direction.1 * half));
+            result.push((right.0 + direction.0 * half, right.1 + direction.1 * half));
+        }
+        CapStyle::Round => {
+            let left = offset(end, direction, half);
+            let from = (left.1 - end.1).atan2(left.0 - end.0);
+            add_arc(result, end, half, from, -PI);
+        }
+    }
+}
+
+/// Add points along an arc, including both ends
+fn add_arc(result: &mut Vec<Vec2>, center: Vec2, radius: f64, from: f64, sweep: f64) {
+    let steps = (sweep.abs() / ARC_STEP).ceil().max(1.0) as usize;
+    for i in 0..=steps {
+        let angle = from + sweep * i as f64 / steps as f64;
+        result.push((
+            center.0 + radius * angle.cos(),
+            center.1 + radius * angle.sin(),
+        ));
+    }
+}
+
+/// Offset `point` along the left normal of `direction`
+fn offset(point: Vec2, direction: Vec2, distance: f64) -> Vec2 {
+    (
+        point.0 - direction.1 * distance,
+        point.1 + direction.0 * distance,
+    )
+}
+
+fn intersect(p: Vec2, r: Vec2, q: Vec2, s: Vec2) -> Option<Vec2> {
+    let denominator = cross(r, s);
+    if denominator.abs() < 1e-9 {
+        return None;
+    }
+    let t = cross((q.0 - p.0, q.1 - p.1), s) / denominator;
+    Some((p.0 + r.0 * t, p.1 + r.1 * t))
+}
+
+fn normalize(v: Vec2) -> Vec2 {
+    let len = length(v.0, v.1);
+    if len == 0.0 {
+        (1.0, 0.0)
+    } else {
+        (v.0 / len, v.1 / len)
+    }
+}
+
+fn neg(v: Vec2) -> Vec2 {
+    (-v.0, -v.1)
+}
+
+fn cross(a: Vec2, b: Vec2) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: Vec2, b: Vec2) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Outline, SegmentLine};
+
+    fn line(points: &[(i32, i32)]) -> PathShape {
+        PathShape {
+            start: Point {
+                x: points[0].0,
+                y: points[0].1,
+            },
+            outlines: vec![Outline::Line(
+                points[1..]
+                    .iter()
+                    .map(|&(x, y)| SegmentLine {
+                        end: Point { x, y },
+                    })
+                    .collect(),
+            )],
+        }
+    }
+
+    fn corners(shape: &PathShape) -> Vec<(i32, i32)> {
+        shape.to_polyline(1).iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    #[test]
+    fn test_straight_stroke_is_rectangle() {
+        let shape = line(&[(0, 0), (1000, 0)]);
+        let outline = shape.stroke_to_outline(200, CapStyle::Butt, JoinStyle::Miter);
+
+        assert_eq!(
+            corners(&outline),
+            vec![(0, 100), (1000, 100), (1000, -100), (0, -100), (0, 100)]
+        );
+    }
+
+    #[test]
+    fn test_square_cap_extends_ends() {
+        let shape = line(&[(0, 0), (1000, 0)]);
+        let outline = shape.stroke_to_outline(200, CapStyle::Square, JoinStyle::Miter);
+        let points = corners(&outline);
+
+        assert_eq!(points.iter().map(|p| p.0).min(), Some(-100));
+        assert_eq!(points.iter().map(|p| p.0).max(), Some(1100));
+    }
+
+    #[test]
+    fn test_miter_join_is_closed() {
+        let shape = line(&[(0, 0), (1000, 0), (1000, 1000)]);
+        let outline = shape.stroke_to_outline(200, CapStyle::Butt, JoinStyle::Miter);
+        let points = corners(&outline);
+
+        assert_eq!(points.first(), points.last());
+        assert!(points.contains(&(1100, -100)));
+        assert!(points.contains(&(900, 100)));
+    }
+}
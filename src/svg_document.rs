@@ -0,0 +1,694 @@
+//! SVG document to FCM shape converter
+//!
+//! Walks the elements of an SVG document and converts every shape element
+//! into fcmlib PathShapes, applying group and element transforms on the way.
+//!
+//! Only geometry is converted. Elements that would contribute to the rendered
+//! artwork but can't be represented as cut paths (text, images, `<use>`
+//! references, gradients, clip paths, …) are skipped and reported by name in
+//! [`SvgDocument::unsupported`].
+//!
+//! The root `viewBox` is not applied, coordinates are converted from user
+//! units according to the [`SvgConfig`].
+//!
+//! # Example
+//! ```
+//! use fcmlib::svg_document::parse_document;
+//! use fcmlib::svg_path::SvgConfig;
+//!
+//! let svg = r#"<svg xmlns="http://www.w3.org/2000/svg">
+//!   <g transform="translate(10, 10)">
+//!     <rect x="0" y="0" width="100" height="50"/>
+//!   </g>
+//!   <text x="0" y="0">Hello</text>
+//! </svg>"#;
+//!
+//! let document = parse_document(svg, &SvgConfig::default()).unwrap();
+//! assert_eq!(document.shapes.len(), 1);
+//! assert_eq!(document.unsupported, vec!["text"]);
+//! ```
+
+use crate::svg_path::{SvgConfig, SvgParseError, SvgPathParser};
+use crate::PathShape;
+
+/// Elements converted to shapes
+const SHAPE_ELEMENTS: &[&str] = &[
+    "path", "rect", "circle", "ellipse", "line", "polygon", "polyline",
+];
+/// Containers whose children are rendered
+const GROUP_ELEMENTS: &[&str] = &["svg", "g", "a", "switch"];
+/// Elements without any visual output
+const SILENT_ELEMENTS: &[&str] = &["title", "desc", "metadata", "style", "script", "stop"];
+
+/// An SVG 2D transform matrix
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    /// Parse an SVG `transform` attribute value
+    pub fn parse(value: &str) -> Result<Transform, SvgParseError> {
+        let mut result = Transform::IDENTITY;
+        let mut rest = value.trim();
+
+        while !rest.is_empty() {
+            let open = rest.find('(').ok_or_else(|| transform_error(value))?;
+            let close = open
+                + rest[open..]
+                    .find(')')
+                    .ok_or_else(|| transform_error(value))?;
+            let name = rest[..open].trim_matches(|c: char| c.is_whitespace() || c == ',');
+            let args: Vec<f64> = rest[open + 1..close]
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|it| !it.is_empty())
+                .map(|it| match it.parse::<f64>() {
+                    Ok(number) if number.is_finite() => Ok(number),
+                    _ => Err(transform_error(value)),
+                })
+                .collect::<Result<_, _>>()?;
+
+            let transform = match (name, args.as_slice()) {
+                ("matrix", &[a, b, c, d, e, f]) => Transform { a, b, c, d, e, f },
+                ("translate", &[x]) => Transform::translate(x, 0.0),
+                ("translate", &[x, y]) => Transform::translate(x, y),
+                ("scale", &[s]) => Transform::scale(s, s),
+                ("scale", &[x, y]) => Transform::scale(x, y),
+                ("rotate", &[angle]) => Transform::rotate(angle),
+                ("rotate", &[angle, cx, cy]) => Transform::translate(cx, cy)
+                    .multiply(&Transform::rotate(angle))
+                    .multiply(&Transform::translate(-cx, -cy)),
+                ("skewX", &[angle]) => Transform {
+                    c: angle.to_radians().tan(),
+                    ..Transform::IDENTITY
+                },
+                ("skewY", &[angle]) => Transform {
+                    b: angle.to_radians().tan(),
+                    ..Transform::IDENTITY
+                },
+                _ => return Err(transform_error(value)),
+            };
+
+            result = result.multiply(&transform);
+            rest = rest[close + 1..].trim_start();
+        }
+
+        Ok(result)
+    }
+
+    pub fn translate(x: f64, y: f64) -> Transform {
+        Transform {
+            e: x,
+            f: y,
+            ..Transform::IDENTITY
+        }
+    }
+
+    pub fn scale(x: f64, y: f64) -> Transform {
+        Transform {
+            a: x,
+            d: y,
+            ..Transform::IDENTITY
+        }
+    }
+
+    /// Rotation by `angle` degrees
+    pub fn rotate(angle: f64) -> Transform {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Transform {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Transform::IDENTITY
+        }
+    }
+
+    /// Combined transform applying `other` first, then `self`
+    pub fn multiply(&self, other: &Transform) -> Transform {
+        Transform {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    /// Transform a point
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+}
+
+fn transform_error(value: &str) -> SvgParseError {
+    SvgParseError {
+        message: format!("Invalid transform: {}", value),
+        position: 0,
+    }
+}
+
+/// Result of converting an SVG document
+#[derive(Debug)]
+pub struct SvgDocument {
+    /// Converted shapes in document order
+    pub shapes: Vec<PathShape>,
+    /// Names of skipped elements, in order of first occurrence
+    pub unsupported: Vec<String>,
+}
+
+/// Convert all shape elements of an SVG document
+pub fn parse_document(svg: &str, config: &SvgConfig) -> Result<SvgDocument, SvgParseError> {
+    enum Frame {
+        Render(Transform),
+        Defs,
+        Skip,
+    }
+
+    let mut document = SvgDocument {
+        shapes: Vec::new(),
+        unsupported: Vec::new(),
+    };
+    fn report(name: &str, document: &mut SvgDocument) {
+        if !document.unsupported.iter().any(|it| it == name) {
+            document.unsupported.push(name.to_string());
+        }
+    }
+
+    let mut stack: Vec<Frame> = Vec::new();
+    for node in scan(svg)? {
+        let element = match node {
+            Node::Close => {
+                stack.pop();
+                continue;
+            }
+            Node::Open(element) => element,
+        };
+        let name = element.name.strip_prefix("svg:").unwrap_or(element.name);
+        let is_silent = SILENT_ELEMENTS.contains(&name) || name.contains(':');
+
+        let frame = match stack.last().unwrap_or(&Frame::Render(Transform::IDENTITY)) {
+            Frame::Skip => Frame::Skip,
+            Frame::Defs => {
+                if !is_silent && !SHAPE_ELEMENTS.contains(&name) && !GROUP_ELEMENTS.contains(&name)
+                {
+                    report(name, &mut document);
+                }
+                Frame::Defs
+            }
+            Frame::Render(transform) => {
+                if GROUP_ELEMENTS.contains(&name) {
+                    match element.attribute("transform") {
+                        Some(value) => Frame::Render(transform.multiply(&Transform::parse(value)?)),
+                        None => Frame::Render(*transform),
+                    }
+                } else if name == "defs" {
+                    Frame::Defs
+                } else if let Some(d) = element.path_data(name) {
+                    let transform = match element.attribute("transform") {
+                        Some(value) => transform.multiply(&Transform::parse(value)?),
+                        None => *transform,
+                    };
+                    let parser = SvgPathParser::new(config.clone()).with_transform(transform);
+                    document.shapes.extend(parser.parse(&d)?);
+                    Frame::Skip
+                } else {
+                    if !is_silent && !SHAPE_ELEMENTS.contains(&name) {
+                        report(name, &mut document);
+                    }
+                    Frame::Skip
+                }
+            }
+        };
+
+        if !element.self_closing {
+            stack.push(frame);
+        }
+    }
+
+    Ok(document)
+}
+
+/// Convert a `<rect>`, with optional rounded corners
+pub fn rect_to_shape(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    rx: f64,
+    ry: f64,
+    config: &SvgConfig,
+) -> Result<PathShape, SvgParseError> {
+    shape_from_path_data(&rect_path_data(x, y, width, height, rx, ry), config)
+}
+
+/// Convert a `<circle>`
+pub fn circle_to_shape(
+    cx: f64,
+    cy: f64,
+    r: f64,
+    config: &SvgConfig,
+) -> Result<PathShape, SvgParseError> {
+    shape_from_path_data(&ellipse_path_data(cx, cy, r, r), config)
+}
+
+/// Convert an `<ellipse>`
+pub fn ellipse_to_shape(
+    cx: f64,
+    cy: f64,
+    rx: f64,
+    ry: f64,
+    config: &SvgConfig,
+) -> Result<PathShape, SvgParseError> {
+    shape_from_path_data(&ellipse_path_data(cx, cy, rx, ry), config)
+}
+
+/// Convert a `<line>`, an open shape with a single segment
+pub fn line_to_shape(
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    config: &SvgConfig,
+) -> Result<PathShape, SvgParseError> {
+    shape_from_path_data(&points_path_data(&[(x1, y1), (x2, y2)], false), config)
+}
+
+/// Convert a `<polyline>`, which stays open. Fails without points.
+pub fn polyline_to_shape(
+    points: &[(f64, f64)],
+    config: &SvgConfig,
+) -> Result<PathShape, SvgParseError> {
+    shape_from_path_data(&points_path_data(points, false), config)
+}
+
+/// Convert a `<polygon>`, which is closed back to its first point. Fails
+/// without points.
+pub fn polygon_to_shape(
+    points: &[(f64, f64)],
+    config: &SvgConfig,
+) -> Result<PathShape, SvgParseError> {
+    shape_from_path_data(&points_path_data(points, true), config)
+}
+
+fn shape_from_path_data(d: &str, config: &SvgConfig) -> Result<PathShape, SvgParseError> {
+    SvgPathParser::new(config.clone())
+        .parse(d)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| SvgParseError {
+            message: String::from("Element has no points"),
+            position: 0,
+        })
+}
+
+fn rect_path_data(x: f64, y: f64, width: f64, height: f64, rx: f64, ry: f64) -> String {
+    // Per SVG, a missing radius takes the value of the other one
+    let (rx, ry) = match (rx > 0.0, ry > 0.0) {
+        (true, false) => (rx, rx),
+        (false, true) => (ry, ry),
+        _ => (rx, ry),
+    };
+    let rx = rx.clamp(0.0, width / 2.0);
+    let ry = ry.clamp(0.0, height / 2.0);
+
+    if rx > 0.0 && ry > 0.0 {
+        format!(
+            "M {},{} H {} A {rx},{ry} 0 0 1 {},{} V {} A {rx},{ry} 0 0 1 {},{} H {} A {rx},{ry} 0 0 1 {},{} V {} A {rx},{ry} 0 0 1 {},{} Z",
+            x + rx, y,
+            x + width - rx,
+            x + width, y + ry,
+            y + height - ry,
+            x + width - rx, y + height,
+            x + rx,
+            x, y + height - ry,
+            y + ry,
+            x + rx, y,
+        )
+    } else {
+        format!("M {},{} H {} V {} H {} Z", x, y, x + width, y + height, x)
+    }
+}
+
+fn ellipse_path_data(cx: f64, cy: f64, rx: f64, ry: f64) -> String {
+    format!(
+        "M {},{} A {rx},{ry} 0 1 0 {},{} A {rx},{ry} 0 1 0 {},{} Z",
+        cx - rx,
+        cy,
+        cx + rx,
+        cy,
+        cx - rx,
+        cy
+    )
+}
+
+fn points_path_data(points: &[(f64, f64)], closed: bool) -> String {
+    let mut d = String::new();
+    for (i, (x, y)) in points.iter().enumerate() {
+        d.push_str(&format!("{} {},{} ", if i == 0 { "M" } else { "L" }, x, y));
+    }
+    if closed && !points.is_empty() {
+        d.push('Z');
+    }
+    d
+}
+
+/// Parse a `points` attribute into coordinate pairs
+fn parse_points(value: &str) -> Vec<(f64, f64)> {
+    let numbers: Vec<f64> = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter_map(|it| it.parse().ok())
+        .collect();
+    numbers.chunks_exact(2).map(|it| (it[0], it[1])).collect()
+}
+
+/// Parse a length attribute, ignoring any unit suffix
+fn parse_length(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+struct Element<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str)>,
+    self_closing: bool,
+}
+
+impl Element<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn length(&self, name: &str) -> f64 {
+        self.attribute(name).and_then(parse_length).unwrap_or(0.0)
+    }
+
+    /// Path data equivalent to this element, if it is a shape element
+    fn path_data(&self, name: &str) -> Option<String> {
+        match name {
+            "path" => self.attribute("d").map(String::from),
+            "rect" => Some(rect_path_data(
+                self.length("x"),
+                self.length("y"),
+                self.length("width"),
+                self.length("height"),
+                self.length("rx"),
+                self.length("ry"),
+            )),
+            "circle" => Some(ellipse_path_data(
+                self.length("cx"),
+                self.length("cy"),
+                self.length("r"),
+                self.length("r"),
+            )),
+            "ellipse" => Some(ellipse_path_data(
+                self.length("cx"),
+                self.length("cy"),
+                self.length("rx"),
+                self.length("ry"),
+            )),
+            "line" => Some(points_path_data(
+                &[
+                    (self.length("x1"), self.length("y1")),
+                    (self.length("x2"), self.length("y2")),
+                ],
+                false,
+            )),
+            "polyline" => Some(points_path_data(
+                &parse_points(self.attribute("points")?),
+                false,
+            )),
+            "polygon" => Some(points_path_data(
+                &parse_points(self.attribute("points")?),
+                true,
+            )),
+            _ => None,
+        }
+    }
+}
+
+enum Node<'a> {
+    Open(Element<'a>),
+    Close,
+}
+
+/// Split an XML document into opening and closing tags, skipping text,
+/// comments, processing instructions and declarations
+fn scan(svg: &str) -> Result<Vec<Node<'_>>, SvgParseError> {
+    let error = |message: &str, position: usize| SvgParseError {
+        message: message.to_string(),
+        position,
+    };
+
+    let mut nodes = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = svg[pos..].find('<') {
+        pos += offset;
+        let rest = &svg[pos..];
+
+        let skip_to = if rest.starts_with("<!--") {
+            Some("-->")
+        } else if rest.starts_with("<![CDATA[") {
+            Some("]]>")
+        } else if rest.starts_with("<?") {
+            Some("?>")
+        } else if rest.starts_with("<!") {
+            Some(">")
+        } else {
+            None
+        };
+        if let Some(end) = skip_to {
+            let length = rest
+                .find(end)
+                .ok_or_else(|| error("Unterminated markup", pos))?;
+            pos += length + end.len();
+            continue;
+        }
+
+        if rest.starts_with("</") {
+            let length = rest
+                .find('>')
+                .ok_or_else(|| error("Unterminated tag", pos))?;
+            nodes.push(Node::Close);
+            pos += length + 1;
+            continue;
+        }
+
+        // Tag name
+        let mut i = 1;
+        let bytes = rest.as_bytes();
+        while i < bytes.len()
+            && !bytes[i].is_ascii_whitespace()
+            && bytes[i] != b'/'
+            && bytes[i] != b'>'
+        {
+            i += 1;
+        }
+        let name = &rest[1..i];
+
+        // Attributes
+        let mut attributes = Vec::new();
+        let self_closing = loop {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if i >= bytes.len() {
+                return Err(error("Unterminated tag", pos));
+            }
+            if rest[i..].starts_with("/>") {
+                i += 2;
+                break true;
+            }
+            if bytes[i] == b'>' {
+                i += 1;
+                break false;
+            }
+
+            let key_start = i;
+            while i < bytes.len()
+                && bytes[i] != b'='
+                && !bytes[i].is_ascii_whitespace()
+                && bytes[i] != b'>'
+            {
+                i += 1;
+            }
+            let key = &rest[key_start..i];
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if i >= bytes.len() || bytes[i] != b'=' {
+                return Err(error("Expected attribute value", pos + i));
+            }
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            let quote = *bytes.get(i).ok_or_else(|| error("Unterminated tag", pos))?;
+            if quote != b'"' && quote != b'\'' {
+                return Err(error("Expected quoted attribute value", pos + i));
+            }
+            let value_start = i + 1;
+            let length = rest[value_start..]
+                .find(quote as char)
+                .ok_or_else(|| error("Unterminated attribute value", pos + i))?;
+            attributes.push((key, &rest[value_start..value_start + length]));
+            i = value_start + length + 1;
+        };
+
+        nodes.push(Node::Open(Element {
+            name,
+            attributes,
+            self_closing,
+        }));
+        pos += i;
+    }
+
+    Ok(nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Outline;
+
+    fn config() -> SvgConfig {
+        // 1 SVG unit = 1 FCM unit
+        SvgConfig {
+            dpi: 2540.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_transform() {
+        let svg = r#"<svg><g transform="translate(100 200) scale(2)"><rect width="10" height="10"/></g></svg>"#;
+        let document = parse_document(svg, &config()).unwrap();
+
+        assert_eq!(document.shapes.len(), 1);
+        let (min, max) = document.shapes[0].bounds();
+        assert_eq!((min.x, min.y, max.x, max.y), (100, 200, 120, 220));
+    }
+
+    #[test]
+    fn test_path_transform() {
+        let svg = r#"<svg><path transform="rotate(90)" d="M 0,0 L 100,0"/></svg>"#;
+        let document = parse_document(svg, &config()).unwrap();
+        let end = document.shapes[0].end_point();
+        assert_eq!((end.x, end.y), (0, 100));
+
+        // The element transform applies before the inherited group transform
+        let svg = r#"<svg><g transform="translate(1000 0)"><path transform="scale(2)" d="M 10,10 L 100,10"/></g></svg>"#;
+        let document = parse_document(svg, &config()).unwrap();
+        let (min, max) = document.shapes[0].bounds();
+        assert_eq!((min.x, min.y, max.x, max.y), (1020, 20, 1200, 20));
+    }
+
+    #[test]
+    fn test_coordinates_out_of_range() {
+        let svg = r#"<svg><g transform="scale(1e300)"><rect width="10" height="10"/></g></svg>"#;
+        assert!(parse_document(svg, &config()).is_err());
+        let svg = r#"<svg><g transform="scale(1e200) scale(1e200)">
+            <path d="M 0,0 L 10,10"/>
+        </g></svg>"#;
+        assert!(parse_document(svg, &config()).is_err());
+        let svg = r#"<svg><g transform="scale(1e400)"><rect width="10" height="10"/></g></svg>"#;
+        assert!(parse_document(svg, &config()).is_err());
+    }
+
+    #[test]
+    fn test_reports_unsupported() {
+        let svg = r##"<?xml version="1.0"?>
+<!-- comment <path d="M 0,0"/> -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <defs>
+    <linearGradient id="grad"><stop offset="0"/></linearGradient>
+    <clipPath id="clip"><rect width="5" height="5"/></clipPath>
+    <path id="shared" d="M 0,0 L 10,0"/>
+  </defs>
+  <title>Test</title>
+  <use xlink:href="#shared"/>
+  <circle cx="50" cy="50" r="10" fill="url(#grad)"/>
+  <text x="0" y="0"><tspan>Hi</tspan></text>
+  <use xlink:href="#shared"/>
+  <polygon points="0,0 10,0 10,10"/>
+</svg>"##;
+        let document = parse_document(svg, &config()).unwrap();
+
+        assert_eq!(document.shapes.len(), 2);
+        assert_eq!(
+            document.unsupported,
+            vec!["linearGradient", "clipPath", "use", "text"]
+        );
+    }
+
+    #[test]
+    fn test_line() {
+        let shape = line_to_shape(0.0, 50.0, 100.0, 50.0, &config()).unwrap();
+        assert_eq!((shape.start.x, shape.start.y), (0, 50));
+        match shape.outlines.as_slice() {
+            [Outline::Line(segments)] => {
+                assert_eq!(segments.len(), 1);
+                assert_eq!((segments[0].end.x, segments[0].end.y), (100, 50));
+            }
+            other => panic!("Expected a single line outline, got {:?}", other),
+        }
+
+        let svg = r#"<svg><line x1="0" y1="50" x2="100" y2="50"/></svg>"#;
+        let document = parse_document(svg, &config()).unwrap();
+        assert_eq!(document.shapes.len(), 1);
+        assert!(document.unsupported.is_empty());
+    }
+
+    #[test]
+    fn test_shape_errors() {
+        assert!(polygon_to_shape(&[], &config()).is_err());
+        assert!(polyline_to_shape(&[], &config()).is_err());
+        assert!(circle_to_shape(f64::NAN, 0.0, 10.0, &config()).is_err());
+        let shape = polygon_to_shape(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)], &config()).unwrap();
+        assert_eq!((shape.start.x, shape.start.y), (0, 0));
+    }
+
+    #[test]
+    fn test_transform_parse() {
+        let transform = Transform::parse("rotate(90)").unwrap();
+        let (x, y) = transform.apply(10.0, 0.0);
+        assert!(x.abs() < 1e-9 && (y - 10.0).abs() < 1e-9);
+
+        assert!(Transform::parse("bogus(1)").is_err());
+    }
+
+    #[test]
+    fn test_transform_parse_malformed() {
+        // A closing parenthesis before the opening one
+        assert!(Transform::parse("a)b(").is_err());
+        assert!(Transform::parse("translate(1 2) )scale(2)").is_err());
+        assert!(Transform::parse("scale(2").is_err());
+        // Arguments that overflow to infinity
+        assert!(Transform::parse("scale(1e400)").is_err());
+        assert!(Transform::parse("translate(1, -1e400)").is_err());
+    }
+}
@@ -15,8 +15,43 @@
 //! let paths = parser.parse("M 0,0 L 100,0 L 100,100 Z").unwrap();
 //! ```
 
+use std::collections::HashMap;
+
+use crate::geometry;
+use crate::svg_document::Transform;
 use crate::{Outline, PathShape, Point, SegmentBezier, SegmentLine};
 
+/// Largest coordinate accepted from SVG input, 10km in FCM units. Leaves room
+/// for the offsets below the `i32` limit.
+const MAX_COORDINATE_FCM: f64 = 1e9;
+
+/// How millimeters are rounded to whole FCM units
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum RoundingMode {
+    /// Nearest unit, with halves rounded away from zero
+    #[default]
+    Round,
+    /// Next unit down, so tiled designs never overlap
+    Floor,
+    /// Next unit up, so tiled designs never leave gaps
+    Ceil,
+}
+
+impl RoundingMode {
+    /// Convert a length in mm to FCM units (hundredths of mm). Values within
+    /// floating point noise of a whole unit count as that unit, so 1.15mm is
+    /// 115 units in every mode.
+    pub fn mm_to_fcm(&self, mm: f64) -> i32 {
+        const NOISE: f64 = 1e-6;
+        let units = mm * 100.0;
+        match self {
+            RoundingMode::Round => units.round() as i32,
+            RoundingMode::Floor => (units + NOISE).floor() as i32,
+            RoundingMode::Ceil => (units - NOISE).ceil() as i32,
+        }
+    }
+}
+
 /// Configuration for SVG to FCM conversion
 #[derive(Debug, Clone)]
 pub struct SvgConfig {
@@ -24,10 +59,19 @@ pub struct SvgConfig {
     pub dpi: f64,
     /// Scale factor (1.0 = no scaling)
     pub scale: f64,
-    /// X offset in mm
+    /// X offset in mm, added after scaling so it is not scaled itself
     pub offset_x_mm: f64,
-    /// Y offset in mm
+    /// Y offset in mm, added after scaling so it is not scaled itself
     pub offset_y_mm: f64,
+    /// Page height in mm to mirror y about, for output whose origin is at the
+    /// bottom left instead of SVG's top left
+    pub flip_y_mm: Option<f64>,
+    /// How coordinates and offsets are rounded to FCM units
+    pub rounding: RoundingMode,
+    /// Reject input that breaks the SVG path grammar but is tolerated by
+    /// default: paths not starting with a moveto, and commas that don't sit
+    /// between two numbers, like doubled, leading or trailing commas
+    pub strict: bool,
 }
 
 impl Default for SvgConfig {
@@ -37,6 +81,9 @@ impl Default for SvgConfig {
             scale: 1.0,
             offset_x_mm: 0.0,
             offset_y_mm: 0.0,
+            flip_y_mm: None,
+            rounding: RoundingMode::Round,
+            strict: false,
         }
     }
 }
@@ -47,14 +94,19 @@ impl SvgConfig {
         // SVG pixels → inches → mm → hundredths of mm
         let inches = svg_value / self.dpi;
         let mm = inches * 25.4 * self.scale;
-        (mm * 100.0).round() as i32
+        self.rounding.mm_to_fcm(mm)
     }
 
-    /// Convert SVG point to FCM Point
+    /// Convert SVG point to FCM Point. The point is scaled first, then moved
+    /// by the offset in absolute mm, then flipped if `flip_y_mm` is set.
     pub fn point_to_fcm(&self, x: f64, y: f64) -> Point {
+        let y = self.to_fcm(y) + self.rounding.mm_to_fcm(self.offset_y_mm);
         Point {
-            x: self.to_fcm(x) + (self.offset_x_mm * 100.0) as i32,
-            y: self.to_fcm(y) + (self.offset_y_mm * 100.0) as i32,
+            x: self.to_fcm(x) + self.rounding.mm_to_fcm(self.offset_x_mm),
+            y: match self.flip_y_mm {
+                Some(height_mm) => self.rounding.mm_to_fcm(height_mm) - y,
+                None => y,
+            },
         }
     }
 }
@@ -62,6 +114,7 @@ impl SvgConfig {
 /// SVG Path parser and converter
 pub struct SvgPathParser {
     config: SvgConfig,
+    transform: Transform,
 }
 
 /// Represents a parsed SVG subpath (one continuous path from M to Z or next M)
@@ -89,7 +142,16 @@ impl std::error::Error for SvgParseError {}
 
 impl SvgPathParser {
     pub fn new(config: SvgConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            transform: Transform::IDENTITY,
+        }
+    }
+
+    /// Apply an SVG transform to all coordinates before converting them
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
     }
 
     /// Parse an SVG path `d` attribute into FCM PathShapes
@@ -107,10 +169,32 @@ impl SvgPathParser {
 
     /// Parse SVG path into subpaths (more detailed output)
     pub fn parse_to_subpaths(&self, d: &str) -> Result<Vec<ParsedSubpath>, SvgParseError> {
-        let tokens = tokenize(d)?;
+        let tokens = tokenize(d, self.config.strict)?;
+        let starts_with_move = matches!(tokens.first(), Some(Token::Command('M' | 'm')) | None);
+        if self.config.strict && !starts_with_move {
+            return Err(SvgParseError {
+                message: "Path must start with a moveto".to_string(),
+                position: 0,
+            });
+        }
         self.parse_tokens(&tokens)
     }
 
+    /// Count the commands in an SVG path `d` attribute by letter, with
+    /// relative commands counted under their uppercase letter. Commands are
+    /// counted as written, so coordinates repeating a command without its
+    /// letter don't add to the count. Only the syntax is checked, not the
+    /// number of arguments.
+    pub fn command_histogram(d: &str) -> Result<HashMap<char, usize>, SvgParseError> {
+        let mut histogram = HashMap::new();
+        for token in tokenize(d, false)? {
+            if let Token::Command(c) = token {
+                *histogram.entry(c.to_ascii_uppercase()).or_insert(0) += 1;
+            }
+        }
+        Ok(histogram)
+    }
+
     fn parse_tokens(&self, tokens: &[Token]) -> Result<Vec<ParsedSubpath>, SvgParseError> {
         let mut subpaths = Vec::new();
         let mut current_x = 0.0f64;
@@ -144,7 +228,7 @@ impl SvgPathParser {
                                     subpath_start_y,
                                     &current_segments,
                                     false,
-                                ));
+                                )?);
                                 current_segments.clear();
                             }
 
@@ -330,22 +414,13 @@ impl SvgPathParser {
                         'Z' => {
                             // ClosePath
                             if has_start && !current_segments.is_empty() {
-                                // Add closing line if needed
-                                if (current_x - subpath_start_x).abs() > 0.001
-                                    || (current_y - subpath_start_y).abs() > 0.001
-                                {
-                                    current_segments.push(Segment::Line {
-                                        x: subpath_start_x,
-                                        y: subpath_start_y,
-                                    });
-                                }
-
+                                // build_subpath adds the closing line if needed
                                 subpaths.push(self.build_subpath(
                                     subpath_start_x,
                                     subpath_start_y,
                                     &current_segments,
                                     true,
-                                ));
+                                )?);
                                 current_segments.clear();
                             }
 
@@ -381,7 +456,7 @@ impl SvgPathParser {
                 subpath_start_y,
                 &current_segments,
                 false,
-            ));
+            )?);
         }
 
         Ok(subpaths)
@@ -423,14 +498,31 @@ impl SvgPathParser {
         Ok((x, y, 2))
     }
 
+    /// Points are checked after the transform, since a large scale can push
+    /// coordinates that parse fine beyond what FCM units can hold
+    fn to_fcm_point(&self, x: f64, y: f64) -> Result<Point, SvgParseError> {
+        let (x, y) = self.transform.apply(x, y);
+        let in_range = |value: f64| {
+            let units = value / self.config.dpi * 2540.0 * self.config.scale;
+            units.is_finite() && units.abs() <= MAX_COORDINATE_FCM
+        };
+        if !in_range(x) || !in_range(y) {
+            return Err(SvgParseError {
+                message: format!("Coordinate out of range: {}, {}", x, y),
+                position: 0,
+            });
+        }
+        Ok(self.config.point_to_fcm(x, y))
+    }
+
     fn build_subpath(
         &self,
         start_x: f64,
         start_y: f64,
         segments: &[Segment],
         closed: bool,
-    ) -> ParsedSubpath {
-        let start = self.config.point_to_fcm(start_x, start_y);
+    ) -> Result<ParsedSubpath, SvgParseError> {
+        let start = self.to_fcm_point(start_x, start_y)?;
 
         // Check if all segments are lines or if we have beziers
         let has_beziers = segments.iter().any(|s| matches!(s, Segment::Cubic { .. }));
@@ -443,20 +535,20 @@ impl SvgPathParser {
                     .map(|seg| match seg {
                         Segment::Line { x, y } => {
                             // Line as degenerate bezier (control points on the line)
-                            let end = self.config.point_to_fcm(*x, *y);
-                            SegmentBezier {
+                            let end = self.to_fcm_point(*x, *y)?;
+                            Ok(SegmentBezier {
                                 control1: end,
                                 control2: end,
                                 end,
-                            }
+                            })
                         }
-                        Segment::Cubic { c1x, c1y, c2x, c2y, x, y } => SegmentBezier {
-                            control1: self.config.point_to_fcm(*c1x, *c1y),
-                            control2: self.config.point_to_fcm(*c2x, *c2y),
-                            end: self.config.point_to_fcm(*x, *y),
-                        },
+                        Segment::Cubic { c1x, c1y, c2x, c2y, x, y } => Ok(SegmentBezier {
+                            control1: self.to_fcm_point(*c1x, *c1y)?,
+                            control2: self.to_fcm_point(*c2x, *c2y)?,
+                            end: self.to_fcm_point(*x, *y)?,
+                        }),
                     })
-                    .collect(),
+                    .collect::<Result<_, SvgParseError>>()?,
             )
         } else {
             // All lines
@@ -464,20 +556,25 @@ impl SvgPathParser {
                 segments
                     .iter()
                     .map(|seg| match seg {
-                        Segment::Line { x, y } => SegmentLine {
-                            end: self.config.point_to_fcm(*x, *y),
-                        },
+                        Segment::Line { x, y } => Ok(SegmentLine {
+                            end: self.to_fcm_point(*x, *y)?,
+                        }),
                         _ => unreachable!(),
                     })
-                    .collect(),
+                    .collect::<Result<_, SvgParseError>>()?,
             )
         };
 
-        ParsedSubpath {
+        let mut outline = outline;
+        if closed {
+            outline.ensure_closed(start, 0);
+        }
+
+        Ok(ParsedSubpath {
             start,
             outline,
             closed,
-        }
+        })
     }
 }
 
@@ -495,16 +592,33 @@ enum Token {
     Number(f64),
 }
 
-/// Tokenize an SVG path string
-fn tokenize(d: &str) -> Result<Vec<Token>, SvgParseError> {
+/// Tokenize an SVG path string. In `strict` mode commas are only accepted
+/// between two numbers.
+fn tokenize(d: &str, strict: bool) -> Result<Vec<Token>, SvgParseError> {
     let mut tokens = Vec::new();
     let mut chars = d.chars().peekable();
     let mut pos = 0;
+    // Position of a comma that still needs a number after it
+    let mut open_comma: Option<usize> = None;
+    let misplaced_comma = |position| SvgParseError {
+        message: "Comma not between two numbers".to_string(),
+        position,
+    };
 
     while let Some(&c) = chars.peek() {
         match c {
             // Whitespace and comma separators
-            ' ' | '\t' | '\n' | '\r' | ',' => {
+            ' ' | '\t' | '\n' | '\r' => {
+                chars.next();
+                pos += 1;
+            }
+            ',' => {
+                if strict {
+                    if open_comma.is_some() || !matches!(tokens.last(), Some(Token::Number(_))) {
+                        return Err(misplaced_comma(pos));
+                    }
+                    open_comma = Some(pos);
+                }
                 chars.next();
                 pos += 1;
             }
@@ -512,6 +626,9 @@ fn tokenize(d: &str) -> Result<Vec<Token>, SvgParseError> {
             // Commands
             'M' | 'm' | 'L' | 'l' | 'H' | 'h' | 'V' | 'v' | 'C' | 'c' | 'S' | 's' | 'Q' | 'q'
             | 'T' | 't' | 'A' | 'a' | 'Z' | 'z' => {
+                if let Some(comma) = open_comma {
+                    return Err(misplaced_comma(comma));
+                }
                 tokens.push(Token::Command(c));
                 chars.next();
                 pos += 1;
@@ -583,12 +700,19 @@ fn tokenize(d: &str) -> Result<Vec<Token>, SvgParseError> {
                     }
                 }
 
-                let num: f64 = num_str.parse().map_err(|_| SvgParseError {
-                    message: format!("Invalid number: {}", num_str),
-                    position: pos,
-                })?;
+                // Numbers too large for f64 parse as infinity, which would end up as
+                // garbage coordinates
+                let num: f64 = num_str
+                    .parse()
+                    .ok()
+                    .filter(|num: &f64| num.is_finite())
+                    .ok_or_else(|| SvgParseError {
+                        message: format!("Invalid number: {}", num_str),
+                        position: pos,
+                    })?;
 
                 tokens.push(Token::Number(num));
+                open_comma = None;
             }
 
             _ => {
@@ -600,10 +724,21 @@ fn tokenize(d: &str) -> Result<Vec<Token>, SvgParseError> {
         }
     }
 
+    if let Some(comma) = open_comma {
+        return Err(misplaced_comma(comma));
+    }
     Ok(tokens)
 }
 
 /// Convert an arc to cubic bezier segments
+///
+/// Degenerate arcs follow the SVG implementation notes (F.6.2): an arc whose
+/// end point equals its start point is omitted entirely, even with the
+/// large-arc flag set, since the ellipse it would lie on is undefined. A full
+/// circle has to be written as two arcs. An arc with a zero radius becomes a
+/// straight line, and so does one with radii over a billion times its chord,
+/// which is either indistinguishable from the line or too large to place.
+#[allow(clippy::too_many_arguments)]
 fn arc_to_beziers(
     x1: f64, y1: f64,
     mut rx: f64, mut ry: f64,
@@ -620,6 +755,12 @@ fn arc_to_beziers(
         return vec![Segment::Line { x: x2, y: y2 }];
     }
 
+    // Squaring huge radii overflows to infinity and the center can't be found
+    let chord = (x2 - x1).hypot(y2 - y1);
+    if rx.abs().min(ry.abs()) > chord * 1e9 {
+        return vec![Segment::Line { x: x2, y: y2 }];
+    }
+
     rx = rx.abs();
     ry = ry.abs();
 
@@ -675,7 +816,7 @@ fn arc_to_beziers(
     }
 
     // Convert arc to bezier curves
-    let n_curves = (dtheta.abs() / (std::f64::consts::PI / 2.0)).ceil() as usize;
+    let n_curves = arc_curve_count(dtheta);
     let dtheta_per_curve = dtheta / n_curves as f64;
 
     let mut segments = Vec::new();
@@ -685,8 +826,7 @@ fn arc_to_beziers(
         let theta_end = current_theta + dtheta_per_curve;
 
         // Bezier control point calculation for arc segment
-        let t = (dtheta_per_curve / 4.0).tan();
-        let alpha = (dtheta_per_curve).sin() * ((4.0 + 3.0 * t * t).sqrt() - 1.0) / 3.0;
+        let alpha = arc_control_factor(dtheta_per_curve);
 
         let cos_t1 = current_theta.cos();
         let sin_t1 = current_theta.sin();
@@ -720,23 +860,115 @@ fn arc_to_beziers(
     segments
 }
 
+/// Number of bezier curves an arc sweeping `dtheta` radians is split into,
+/// at most a quarter turn each
+fn arc_curve_count(dtheta: f64) -> usize {
+    ((dtheta.abs() / (std::f64::consts::PI / 2.0)).ceil() as usize).max(1)
+}
+
+/// Control point distance, as a fraction of the tangent length, for a cubic
+/// bezier approximating a circular arc of `dtheta` radians
+fn arc_control_factor(dtheta: f64) -> f64 {
+    4.0 / 3.0 * (dtheta / 4.0).tan()
+}
+
+/// Maximum distance between an elliptical arc with radii `rx`/`ry` sweeping
+/// `sweep_degrees` and the bezier curves it is converted to, in the same
+/// units as the radii. For ellipses this is an upper bound.
+///
+/// A quarter circle is approximated by a single curve that deviates by about
+/// 0.027% of the radius.
+pub fn arc_max_error(rx: f64, ry: f64, sweep_degrees: f64) -> f64 {
+    let dtheta = sweep_degrees.to_radians();
+    let step = dtheta / arc_curve_count(dtheta) as f64;
+    let alpha = arc_control_factor(step);
+
+    // One curve of the unit circle from angle 0 to `step`; the others are rotations of it
+    let p0 = (1.0, 0.0);
+    let p1 = (1.0, alpha);
+    let p3 = (step.cos(), step.sin());
+    let p2 = (p3.0 + alpha * step.sin(), p3.1 - alpha * step.cos());
+
+    let error = (0..=100)
+        .map(|i| {
+            let (x, y) = geometry::cubic_point(p0, p1, p2, p3, i as f64 / 100.0);
+            ((x * x + y * y).sqrt() - 1.0).abs()
+        })
+        .fold(0.0, f64::max);
+    error * rx.abs().max(ry.abs())
+}
+
 /// Calculate angle between two vectors
 fn angle(ux: f64, uy: f64, vx: f64, vy: f64) -> f64 {
+    // atan2 doesn't need the vector lengths, which underflow to zero for huge radii
     let dot = ux * vx + uy * vy;
-    let len = (ux * ux + uy * uy).sqrt() * (vx * vx + vy * vy).sqrt();
-    let mut angle = (dot / len).clamp(-1.0, 1.0).acos();
-
-    if ux * vy - uy * vx < 0.0 {
-        angle = -angle;
-    }
-
-    angle
+    let cross = ux * vy - uy * vx;
+    cross.atan2(dot)
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
+    /// Parser where 1 SVG unit is 1 FCM unit
+    pub(crate) fn unit_parser() -> SvgPathParser {
+        SvgPathParser::new(SvgConfig {
+            dpi: 2540.0,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        let lenient = SvgPathParser::new(SvgConfig::default());
+        let strict = SvgPathParser::new(SvgConfig {
+            strict: true,
+            ..Default::default()
+        });
+
+        for d in [
+            "L 10,10 20,20",
+            "M 0,0 L 10,,10 Z",
+            "M,0,0 L 10,10 Z",
+            "M 0,0 L 10,10, Z",
+            "M 0,0 L 10,10 20,0,",
+        ] {
+            assert!(lenient.parse(d).is_ok(), "{}", d);
+            assert!(strict.parse(d).is_err(), "{}", d);
+        }
+        let error = strict.parse("M 0,0 L 10,,10").unwrap_err();
+        assert_eq!(error.position, 11);
+
+        // Valid paths, including repeated commands and separators left out
+        for d in ["", "M 0,0 L 10,10 20,0 Z", "m0-5l10.5.5h-3z", "M 0 0 10 10 20 0"] {
+            assert_eq!(
+                lenient.parse(d).unwrap().len(),
+                strict.parse(d).unwrap().len(),
+                "{}",
+                d
+            );
+        }
+        // Wrong argument counts fail in both modes
+        assert!(lenient.parse("M 0,0 L 10").is_err());
+        assert!(strict.parse("M 0,0 L 10").is_err());
+    }
+
+    #[test]
+    fn test_command_histogram() {
+        let histogram = SvgPathParser::command_histogram(
+            "M 0,0 C 10,0 20,10 20,20 c 0,10 -10,20 -20,20 L 0,10 C 0,5 0,0 5,0 l -5,0 z",
+        )
+        .unwrap();
+        assert_eq!(histogram.len(), 4);
+        assert_eq!(histogram[&'M'], 1);
+        assert_eq!(histogram[&'C'], 3);
+        assert_eq!(histogram[&'L'], 2);
+        assert_eq!(histogram[&'Z'], 1);
+
+        assert!(SvgPathParser::command_histogram("").unwrap().is_empty());
+        assert!(SvgPathParser::command_histogram("M 0,0 L 1e,0").is_err());
+    }
+
     #[test]
     fn test_simple_rect() {
         let parser = SvgPathParser::new(SvgConfig {
@@ -787,4 +1019,305 @@ mod tests {
             _ => panic!("Expected bezier outline"),
         }
     }
+
+    fn numbers(d: &str) -> Vec<f64> {
+        tokenize(d, false)
+            .unwrap()
+            .into_iter()
+            .map(|token| match token {
+                Token::Number(n) => n,
+                Token::Command(c) => panic!("Unexpected command {}", c),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_exponent_numbers() {
+        assert_eq!(numbers("1e3"), vec![1000.0]);
+        assert_eq!(numbers("10e2"), vec![1000.0]);
+        assert_eq!(numbers("1.5e-2"), vec![0.015]);
+        assert_eq!(numbers("1E+2-3e1"), vec![100.0, -30.0]);
+    }
+
+    #[test]
+    fn test_signed_and_trailing_dot_numbers() {
+        assert_eq!(numbers("+5"), vec![5.0]);
+        assert_eq!(numbers("-0"), vec![0.0]);
+        assert_eq!(numbers("5."), vec![5.0]);
+        assert_eq!(numbers("5.-3.+2"), vec![5.0, -3.0, 2.0]);
+        assert_eq!(numbers("5..5"), vec![5.0, 0.5]);
+        assert!(tokenize(".", false).is_err());
+    }
+
+    #[test]
+    fn test_trailing_dot_coordinates() {
+        let parser = unit_parser();
+
+        let result = parser.parse("M5.,5. L+10,-0").unwrap();
+        assert_eq!((result[0].start.x, result[0].start.y), (5, 5));
+        let end = result[0].end_point();
+        assert_eq!((end.x, end.y), (10, 0));
+    }
+
+    #[test]
+    fn test_out_of_range_numbers() {
+        assert!(tokenize("1e999", false).is_err());
+        assert!(tokenize("-1e400", false).is_err());
+    }
+
+    #[test]
+    fn test_degenerate_arcs_stay_finite() {
+        let parser = unit_parser();
+
+        for d in [
+            "M 0,0 A 1e-9,1e-9 0 0 1 1000,0",
+            "M 0,0 A 1e300,1e300 0 0 1 1000,0",
+            "M 0,0 A 1e300,1e-300 45 1 1 1000,1000",
+            "M 0,0 A 1000,1000 0 0 1 0,1e-12",
+        ] {
+            for shape in parser.parse(d).unwrap() {
+                let (min, max) = shape.bounds();
+                for value in [min.x, min.y, max.x, max.y] {
+                    assert!((value as i64).abs() <= 2000, "{} gave {}", d, value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exponent_coordinates() {
+        let parser = unit_parser();
+
+        let result = parser.parse_to_subpaths("M1e2,1e2 L2e2,1e2").unwrap();
+        assert_eq!((result[0].start.x, result[0].start.y), (100, 100));
+    }
+
+    #[test]
+    fn test_smooth_quadratic_reflects_quadratic_control() {
+        let parser = unit_parser();
+
+        for d in ["M 0,0 Q 300,300 600,0 T 1200,0", "M 0,0 q 300,300 600,0 t 600,0"] {
+            let result = parser.parse(d).unwrap();
+            let segments = match &result[0].outlines[0] {
+                Outline::Bezier(segments) => segments,
+                _ => panic!("Expected bezier outline"),
+            };
+            assert_eq!(segments.len(), 2);
+
+            // Q control (300,300) reflected through (600,0) gives (900,-300),
+            // which the cubic conversion places two thirds along each side
+            let xy = |p: Point| (p.x, p.y);
+            assert_eq!(xy(segments[0].control2), (400, 200));
+            assert_eq!(xy(segments[1].control1), (800, -200));
+            assert_eq!(xy(segments[1].control2), (1000, -200));
+
+            // Smooth joint: the controls around (600,0) mirror each other
+            assert_eq!(segments[0].control2.x + segments[1].control1.x, 1200);
+            assert_eq!(segments[0].control2.y + segments[1].control1.y, 0);
+        }
+    }
+
+    #[test]
+    fn test_smooth_quadratic_without_quadratic() {
+        let parser = unit_parser();
+
+        // Without a preceding Q/T the control point is the current point, a straight line
+        let result = parser.parse("M 0,0 L 600,0 T 1200,0").unwrap();
+        let (min, max) = result[0].bounds();
+        assert_eq!((min.y, max.y), (0, 0));
+    }
+
+    #[test]
+    fn test_mixed_case_commands() {
+        let parser = unit_parser();
+
+        let result = parser.parse("M0,0 l10,0 L20,0 l0,5").unwrap();
+        let Outline::Line(segments) = &result[0].outlines[0] else {
+            panic!("Expected line outline");
+        };
+        let points: Vec<_> = segments.iter().map(|s| (s.end.x, s.end.y)).collect();
+        assert_eq!(points, vec![(10, 0), (20, 0), (20, 5)]);
+
+        // A smooth curve still reflects the control point of a curve in the other case
+        let result = parser.parse("M0,0 c0,10 10,10 10,0 S20,-10 20,0").unwrap();
+        let Outline::Bezier(segments) = &result[0].outlines[0] else {
+            panic!("Expected bezier outline");
+        };
+        let control = segments[1].control1;
+        assert_eq!((control.x, control.y), (10, -10));
+    }
+
+    #[test]
+    fn test_smooth_cubic_after_line() {
+        let parser = unit_parser();
+
+        // Nothing to reflect after a line, so the first control is the current point
+        let result = parser.parse("M0,0 L10,0 S20,10 30,0").unwrap();
+        let Outline::Bezier(segments) = &result[0].outlines[0] else {
+            panic!("Expected bezier outline");
+        };
+        let curve = segments.last().unwrap();
+        assert_eq!((curve.control1.x, curve.control1.y), (10, 0));
+        assert_eq!((curve.control2.x, curve.control2.y), (20, 10));
+        assert_eq!((curve.end.x, curve.end.y), (30, 0));
+
+        // Or the start point right after a move
+        let result = parser.parse("M5,5 S20,10 30,0").unwrap();
+        let Outline::Bezier(segments) = &result[0].outlines[0] else {
+            panic!("Expected bezier outline");
+        };
+        assert_eq!((segments[0].control1.x, segments[0].control1.y), (5, 5));
+    }
+
+    #[test]
+    fn test_flip_y() {
+        let parser = SvgPathParser::new(SvgConfig {
+            dpi: 2540.0,
+            flip_y_mm: Some(100.0),
+            ..Default::default()
+        });
+
+        let result = parser.parse("M 0,1000 L 500,2000").unwrap();
+        assert_eq!((result[0].start.x, result[0].start.y), (0, 9000));
+        let end = result[0].end_point();
+        assert_eq!((end.x, end.y), (500, 8000));
+    }
+
+    #[test]
+    fn test_offset_after_scale() {
+        let config = SvgConfig {
+            dpi: 2540.0,
+            scale: 2.0,
+            offset_x_mm: 10.0,
+            offset_y_mm: 1.13,
+            ..Default::default()
+        };
+
+        let point = config.point_to_fcm(100.0, 100.0);
+        assert_eq!((point.x, point.y), (1200, 313));
+    }
+
+    #[test]
+    fn test_rounding_mode() {
+        let config = |rounding| SvgConfig {
+            dpi: 25.4,
+            rounding,
+            ..Default::default()
+        };
+
+        // 1 SVG unit = 1mm, so 1.236 is 123.6 FCM units
+        assert_eq!(config(RoundingMode::Round).to_fcm(1.236), 124);
+        assert_eq!(config(RoundingMode::Floor).to_fcm(1.236), 123);
+        assert_eq!(config(RoundingMode::Ceil).to_fcm(1.236), 124);
+        assert_eq!(config(RoundingMode::Round).to_fcm(-1.236), -124);
+        assert_eq!(config(RoundingMode::Floor).to_fcm(-1.236), -124);
+        assert_eq!(config(RoundingMode::Ceil).to_fcm(-1.236), -123);
+
+        // 1.15 * 100 is just below 115 in floating point
+        assert_eq!(RoundingMode::Floor.mm_to_fcm(1.15), 115);
+        assert_eq!(RoundingMode::Ceil.mm_to_fcm(1.15), 115);
+    }
+
+    #[test]
+    fn test_arc_max_error() {
+        let error = arc_max_error(1000.0, 1000.0, 90.0);
+        assert!((error - 0.27).abs() < 0.01, "error {}", error);
+
+        // Splitting into quarter arcs keeps the error of larger sweeps the same
+        assert!((arc_max_error(1000.0, 1000.0, 270.0) - error).abs() < 1e-9);
+        assert!(arc_max_error(1000.0, 1000.0, 45.0) < error);
+    }
+
+    #[test]
+    fn test_arc_follows_circle() {
+        let segments = arc_to_beziers(1000.0, 0.0, 1000.0, 1000.0, 0.0, false, true, 0.0, 1000.0);
+        let [Segment::Cubic { c1x, c1y, c2x, c2y, x, y }] = segments.as_slice() else {
+            panic!("Expected a single cubic");
+        };
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            let (px, py) =
+                geometry::cubic_point((1000.0, 0.0), (*c1x, *c1y), (*c2x, *c2y), (*x, *y), t);
+            assert!(((px * px + py * py).sqrt() - 1000.0).abs() < 0.3);
+        }
+    }
+
+    #[test]
+    fn test_rotated_arc_flags() {
+        // Ellipse with radii 200/100 rotated by 45°, centered on (500, 300)
+        let (rx, ry) = (200.0, 100.0);
+        let (sin, cos) = 45f64.to_radians().sin_cos();
+        let on_ellipse = |(cx, cy): (f64, f64), degrees: f64| {
+            let (t_sin, t_cos) = degrees.to_radians().sin_cos();
+            let (x, y) = (rx * t_cos, ry * t_sin);
+            (cx + x * cos - y * sin, cy + x * sin + y * cos)
+        };
+        let center = (500.0, 300.0);
+        let start = on_ellipse(center, 0.0);
+        let end = on_ellipse(center, 90.0);
+        // The other ellipse through both points, mirrored about the chord's midpoint
+        let mirrored = (start.0 + end.0 - center.0, start.1 + end.1 - center.1);
+
+        // Flags with the center and angles of the expected arc. Positive
+        // angles run clockwise on screen, like the sweep flag.
+        let cases = [
+            (false, true, center, 0.0, 90.0),
+            (true, false, center, 0.0, -270.0),
+            (false, false, mirrored, 270.0, 180.0),
+            (true, true, mirrored, 270.0, 540.0),
+        ];
+        for (large_arc, sweep, arc_center, from, to) in cases {
+            let segments = arc_to_beziers(
+                start.0, start.1, rx, ry, 45.0, large_arc, sweep, end.0, end.1,
+            );
+
+            let mut samples = vec![];
+            let mut previous = start;
+            for segment in &segments {
+                let Segment::Cubic { c1x, c1y, c2x, c2y, x, y } = *segment else {
+                    panic!("Expected cubics");
+                };
+                for i in 0..=1000 {
+                    let t = i as f64 / 1000.0;
+                    let point = geometry::cubic_point(previous, (c1x, c1y), (c2x, c2y), (x, y), t);
+                    samples.push(point);
+                }
+                previous = (x, y);
+            }
+            assert!((previous.0 - end.0).abs() < 1e-6 && (previous.1 - end.1).abs() < 1e-6);
+
+            for i in 0..=50 {
+                let (px, py) = on_ellipse(arc_center, from + (to - from) * i as f64 / 50.0);
+                let distance = samples
+                    .iter()
+                    .map(|(x, y)| (x - px).hypot(y - py))
+                    .fold(f64::MAX, f64::min);
+                assert!(
+                    distance < 0.5,
+                    "large_arc {} sweep {}: ({}, {}) is {} off the arc",
+                    large_arc, sweep, px, py, distance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_coincident_arc_is_omitted() {
+        for large_arc in [false, true] {
+            for sweep in [false, true] {
+                let segments =
+                    arc_to_beziers(10.0, 10.0, 50.0, 50.0, 0.0, large_arc, sweep, 10.0, 10.0);
+                assert!(segments.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_radius_arc_is_line() {
+        let segments = arc_to_beziers(0.0, 0.0, 0.0, 50.0, 0.0, true, true, 100.0, 0.0);
+        assert!(matches!(
+            segments.as_slice(),
+            [Segment::Line { x, y }] if *x == 100.0 && *y == 0.0
+        ));
+    }
 }
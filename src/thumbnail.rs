@@ -0,0 +1,377 @@
+//! Thumbnail rendering for FCM file headers
+//!
+//! Canvas Workspace stores a small monochrome BMP preview of the design in
+//! the file header, which the machine shows in its file browser. Older files
+//! use 88×88 previews, newer ones 92×100 or, for long designs, 78×128. The
+//! size is only stored in the BMP header, so [`decode`] reads it from there.
+//!
+//! # Example
+//! ```
+//! use fcmlib::{thumbnail, Piece};
+//!
+//! let piece = Piece::from_paths(vec![]);
+//! let bmp = thumbnail::render_piece(&piece, thumbnail::WIDTH, thumbnail::HEIGHT);
+//! assert_eq!(&bmp[0..2], b"BM");
+//! ```
+
+use crate::{geometry, piece, Error, Path, Piece, Point};
+
+/// Default thumbnail width in pixels
+pub const WIDTH: usize = 88;
+/// Default thumbnail height in pixels
+pub const HEIGHT: usize = 88;
+/// Pixels kept blank around the drawing on each side
+const MARGIN: usize = 4;
+/// Size of the file header, DIB header and two-color palette
+const HEADER_SIZE: usize = 62;
+/// Pixels per meter written to the header (96 DPI)
+const RESOLUTION: u32 = 3780;
+
+/// Colors of the two palette entries as blue, green, red and a reserved byte.
+/// Pixel bits of 0 are black and 1 are white.
+///
+/// The BMP format asks for a reserved byte of 0, which files saved on the
+/// machine use. Canvas Workspace writes 0xff instead, and the machine shows
+/// both the same way. The Canvas Workspace bytes are kept so generated
+/// headers match its files.
+pub const PALETTE: [[u8; 4]; 2] = [[0x00, 0x00, 0x00, 0xff], [0xff, 0xff, 0xff, 0xff]];
+
+/// A decoded monochrome thumbnail
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    pub width: usize,
+    pub height: usize,
+    /// Whether each pixel is black, in rows from the top left
+    pub pixels: Vec<bool>,
+}
+
+impl Thumbnail {
+    /// Whether the pixel at `x`, `y` from the top left is black
+    pub fn is_black(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x]
+    }
+}
+
+/// Decode a 1-bit BMP like the ones stored in file headers, taking the size
+/// from its DIB header. Both bottom-up and top-down row orders are read, and
+/// a palette color counts as black when it is darker than mid gray.
+pub fn decode(bmp: &[u8]) -> Result<Thumbnail, Error> {
+    let invalid = |message: &str| Error {
+        message: format!("Invalid thumbnail: {0}", message),
+    };
+    let read_u32 = |offset: usize| {
+        bmp.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(|| invalid("truncated header"))
+    };
+
+    if bmp.get(0..2) != Some(b"BM".as_slice()) {
+        return Err(invalid("missing BMP signature"));
+    }
+    let pixel_data = read_u32(10)? as usize;
+    let dib_size = read_u32(14)? as usize;
+    let width = read_u32(18)? as i32;
+    let height = read_u32(22)? as i32;
+    let bits = bmp.get(28..30).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    if dib_size < 40 || width <= 0 || height == 0 {
+        return Err(invalid("unsupported DIB header"));
+    }
+    if bits != Some(1) || read_u32(30)? != 0 {
+        return Err(invalid("only uncompressed 1-bit images are supported"));
+    }
+    let palette = 14 + dib_size;
+    let dark: Vec<bool> = (0..2)
+        .map(|i| {
+            bmp.get(palette + i * 4..palette + i * 4 + 3)
+                .map(|bgr| bgr.iter().map(|&c| c as u32).sum::<u32>() < 3 * 128)
+                .ok_or_else(|| invalid("truncated palette"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let (width, rows) = (width as usize, height.unsigned_abs() as usize);
+    let stride = row_bytes(width);
+    if bmp.len() < pixel_data + rows * stride {
+        return Err(invalid("truncated pixel data"));
+    }
+    let mut pixels = Vec::with_capacity(width * rows);
+    for y in 0..rows {
+        // Positive heights are stored bottom-up
+        let row = if height > 0 { rows - 1 - y } else { y };
+        let start = pixel_data + row * stride;
+        for x in 0..width {
+            let index = (bmp[start + x / 8] >> (7 - x % 8)) & 1;
+            pixels.push(dark[index as usize]);
+        }
+    }
+    Ok(Thumbnail {
+        width,
+        height: rows,
+        pixels,
+    })
+}
+
+/// Render the paths of a piece as a monochrome BMP
+pub fn render_piece(piece: &Piece, width: usize, height: usize) -> Vec<u8> {
+    render_paths(&piece.paths, width, height)
+}
+
+/// Render paths as a monochrome BMP, scaled to fit. Paths without extent,
+/// like a single point, are drawn as a dot at the center.
+pub fn render_paths(paths: &[Path], width: usize, height: usize) -> Vec<u8> {
+    // Start with white image (all 1s = white in 1-bit BMP)
+    let mut bitmap = Bitmap {
+        width,
+        height,
+        pixels: vec![0xFFu8; height * row_bytes(width)],
+    };
+
+    if let Some((min, max)) = piece::paths_bounds(paths) {
+        let extent_x = (max.x - min.x) as f64;
+        let extent_y = (max.y - min.y) as f64;
+        let extent = extent_x.max(extent_y);
+
+        // Scale to fit inside the margin, a single point is drawn at the center
+        let scale = if extent > 0.0 {
+            width.min(height).saturating_sub(2 * MARGIN) as f64 / extent
+        } else {
+            0.0
+        };
+        let offset_x = (width as f64 - extent_x * scale) / 2.0;
+        let offset_y = (height as f64 - extent_y * scale) / 2.0;
+
+        // Transform FCM coords to thumbnail coords
+        let transform = |p: Point| -> (i32, i32) {
+            (
+                ((p.x - min.x) as f64 * scale + offset_x) as i32,
+                ((p.y - min.y) as f64 * scale + offset_y) as i32,
+            )
+        };
+
+        // Flatten curves to roughly a pixel
+        let tolerance = if scale > 0.0 {
+            (1.0 / scale) as i32
+        } else {
+            geometry::DEFAULT_TOLERANCE
+        };
+
+        for shape in paths.iter().filter_map(|it| it.shape.as_ref()) {
+            let points = shape.to_polyline(tolerance);
+            let (mut x0, mut y0) = transform(points[0]);
+            bitmap.set_pixel(x0, y0);
+            for point in &points[1..] {
+                let (x1, y1) = transform(*point);
+                bitmap.draw_line(x0, y0, x1, y1);
+                (x0, y0) = (x1, y1);
+            }
+        }
+    }
+
+    // Combine header and pixels
+    let mut bmp = bmp_header(width, height);
+    bmp.extend_from_slice(&bitmap.pixels);
+    bmp
+}
+
+/// Bytes per pixel row, padded to a multiple of 4
+fn row_bytes(width: usize) -> usize {
+    width.div_ceil(32) * 4
+}
+
+/// BMP file header, DIB header and palette for a bottom-up 1-bit image
+fn bmp_header(width: usize, height: usize) -> Vec<u8> {
+    let file_size = HEADER_SIZE + height * row_bytes(width);
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(b"BM");
+    header.extend_from_slice(&(file_size as u32).to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes()); // Reserved
+    header.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes()); // Pixel data offset
+    header.extend_from_slice(&40u32.to_le_bytes()); // DIB header size
+    header.extend_from_slice(&(width as u32).to_le_bytes());
+    header.extend_from_slice(&(height as u32).to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes()); // Color planes
+    header.extend_from_slice(&1u16.to_le_bytes()); // Bits per pixel
+    header.extend_from_slice(&0u32.to_le_bytes()); // Compression: none
+    header.extend_from_slice(&0u32.to_le_bytes()); // Image size (can be 0 for uncompressed)
+    header.extend_from_slice(&RESOLUTION.to_le_bytes()); // Horizontal resolution
+    header.extend_from_slice(&RESOLUTION.to_le_bytes()); // Vertical resolution
+    header.extend_from_slice(&2u32.to_le_bytes()); // Colors in palette
+    header.extend_from_slice(&2u32.to_le_bytes()); // Important colors
+    for entry in PALETTE {
+        header.extend_from_slice(&entry);
+    }
+    header
+}
+
+struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Set a pixel to black
+    fn set_pixel(&mut self, x: i32, y: i32) {
+        if x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32 {
+            // BMP is bottom-up, so flip y
+            let row = self.height - 1 - y as usize;
+            let col = x as usize;
+            let byte_idx = row * row_bytes(self.width) + col / 8;
+            let bit_idx = 7 - (col % 8);
+            self.pixels[byte_idx] &= !(1 << bit_idx); // Clear bit = black
+        }
+    }
+
+    /// Draw line using Bresenham's algorithm
+    fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let mut x = x0;
+        let mut y = y0;
+
+        loop {
+            self.set_pixel(x, y);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Outline, PathShape, PathTool, SegmentLine};
+
+    fn read_u32(bmp: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bmp[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_default_header() {
+        let bmp = render_paths(&[], WIDTH, HEIGHT);
+        assert_eq!(bmp.len(), 1118);
+        assert_eq!(read_u32(&bmp, 2), 1118);
+        assert_eq!(read_u32(&bmp, 18), 88);
+        assert_eq!(read_u32(&bmp, 22), 88);
+
+        // Same header as written by Canvas Workspace
+        assert_eq!(
+            &bmp[..62],
+            &[
+                0x42, 0x4d, 0x5e, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3e, 0x00, 0x00, 0x00,
+                0x28, 0x00, 0x00, 0x00, 0x58, 0x00, 0x00, 0x00, 0x58, 0x00, 0x00, 0x00, 0x01, 0x00,
+                0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc4, 0x0e, 0x00, 0x00,
+                0xc4, 0x0e, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0xff, 0xff, 0xff, 0xff, 0xff,
+            ][..]
+        );
+    }
+
+    #[test]
+    fn test_custom_size() {
+        let bmp = render_paths(&[], 128, 96);
+
+        // 128 bits = 16 bytes per row, already a multiple of 4
+        assert_eq!(bmp.len(), 62 + 96 * 16);
+        assert_eq!(&bmp[0..2], b"BM");
+        assert_eq!(read_u32(&bmp, 2) as usize, bmp.len());
+        assert_eq!(read_u32(&bmp, 10), 62);
+        assert_eq!(read_u32(&bmp, 18), 128);
+        assert_eq!(read_u32(&bmp, 22), 96);
+        assert_eq!(&bmp[28..30], &[1, 0]);
+    }
+
+    #[test]
+    fn test_decode_square() {
+        let line = |x, y| SegmentLine {
+            end: Point { x, y },
+        };
+        let square = Path {
+            tool: PathTool::TOOL_CUT,
+            shape: Some(PathShape {
+                start: Point { x: 0, y: 0 },
+                outlines: vec![Outline::Line(vec![
+                    line(1000, 0),
+                    line(1000, 1000),
+                    line(0, 1000),
+                    line(0, 0),
+                ])],
+            }),
+            rhinestone_diameter: None,
+            rhinestones: vec![],
+        };
+        let bmp = render_paths(&[square], WIDTH, HEIGHT);
+
+        // Palette index 0 is black and 1 is white, ignoring the reserved byte
+        assert_eq!(read_u32(&bmp, 46), 2);
+        assert_eq!(&bmp[54..57], &[0x00, 0x00, 0x00]);
+        assert_eq!(&bmp[58..61], &[0xff, 0xff, 0xff]);
+
+        // Color of a pixel counted from the top left, in the bottom-up rows
+        let pixel_data = read_u32(&bmp, 10) as usize;
+        let color = |x: usize, y: usize| {
+            let byte = bmp[pixel_data + (HEIGHT - 1 - y) * row_bytes(WIDTH) + x / 8];
+            let index = (byte >> (7 - x % 8)) & 1;
+            &bmp[54 + index as usize * 4..57 + index as usize * 4]
+        };
+        // The outline runs along the margin with the inside left white
+        assert_eq!(color(MARGIN, MARGIN), &[0x00, 0x00, 0x00]);
+        assert_eq!(color(WIDTH - MARGIN, HEIGHT / 2), &[0x00, 0x00, 0x00]);
+        assert_eq!(color(WIDTH / 2, HEIGHT / 2), &[0xff, 0xff, 0xff]);
+        assert_eq!(color(0, 0), &[0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn test_decode_sizes() {
+        let square = Path {
+            tool: PathTool::TOOL_CUT,
+            shape: Some(PathShape::from_fcm_points(
+                &[
+                    Point { x: 0, y: 0 },
+                    Point { x: 1000, y: 0 },
+                    Point { x: 1000, y: 1000 },
+                ],
+                true,
+            )),
+            rhinestone_diameter: None,
+            rhinestones: vec![],
+        };
+        for (width, height) in [(88, 88), (92, 100), (78, 128)] {
+            let bmp = render_paths(std::slice::from_ref(&square), width, height);
+            let thumbnail = decode(&bmp).unwrap();
+            assert_eq!((thumbnail.width, thumbnail.height), (width, height));
+            assert_eq!(thumbnail.pixels.len(), width * height);
+            // The square is centered, its top edge starts at the margin
+            let top = (height - (width - 2 * MARGIN)) / 2;
+            assert!(thumbnail.is_black(MARGIN, top));
+            assert!(!thumbnail.is_black(0, 0));
+            assert!(!thumbnail.is_black(width, 0));
+        }
+
+        assert!(decode(b"BM").is_err());
+        let mut bmp = render_paths(&[], 78, 128);
+        bmp.truncate(bmp.len() - 1);
+        assert!(decode(&bmp).is_err());
+    }
+
+    #[test]
+    fn test_row_padding() {
+        assert_eq!(row_bytes(88), 12);
+        assert_eq!(row_bytes(92), 12);
+        assert_eq!(row_bytes(100), 16);
+        assert_eq!(row_bytes(128), 16);
+    }
+}
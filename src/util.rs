@@ -68,10 +68,11 @@ pub(crate) fn read_from_offsets<'a, O, E, A>(
 }
 
 pub fn write_utf16_str(data: &str, buffer: &mut Vec<u8>) -> std::io::Result<()> {
-    let mut data = String::from(data);
+    // The length is a count of UTF-16 code units, not bytes
+    let mut data: Vec<u16> = data.encode_utf16().collect();
     data.truncate(255);
     (data.len() as u8).encode(buffer)?;
-    for char in data.encode_utf16() {
+    for char in data {
         char.encode(buffer)?;
     }
     Ok(())
@@ -1,10 +1,11 @@
-use fcmlib::FcmFile;
+use fcmlib::{scan_directory, FcmFile, FileSource, FileType, FileVariant};
 
 fn test_file(path: &str) -> FcmFile {
     let original = std::fs::read(path).unwrap();
     let parsed = FcmFile::from_bytes(original.as_slice()).unwrap();
     let serialized = parsed.to_bytes().unwrap();
     assert_eq!(serialized, original);
+    parsed.validate().unwrap();
     parsed
 }
 
@@ -5642,3 +5643,56 @@ fn parses_test_text() {
 fn parses_test_thicc() {
     test_file("tests/samples/test/thicc.fcm");
 }
+
+fn assert_source(dir: &str, source: FileSource) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let file = FcmFile::from_file(&path).unwrap();
+        assert_eq!(file.source(), source, "{}", path.display());
+    }
+}
+
+#[test]
+fn detects_source_brother() {
+    assert_source("tests/samples/brother", FileSource::CanvasWorkspace);
+}
+
+#[test]
+fn detects_source_scan() {
+    assert_source("tests/samples/scan", FileSource::Machine);
+}
+
+#[test]
+fn scans_test_directory() {
+    let summaries = scan_directory("tests/samples/test").unwrap();
+    assert_eq!(summaries.len(), 31);
+    assert_eq!(summaries.iter().map(|s| s.piece_count).sum::<u32>(), 36);
+
+    let print_and_cut: Vec<_> = summaries.iter().filter(|s| s.is_print_and_cut).collect();
+    assert_eq!(print_and_cut.len(), 1);
+    assert_eq!(
+        print_and_cut[0].name,
+        "23 February New Print to Cut Test.fcm"
+    );
+    assert_eq!(print_and_cut[0].variant, FileVariant::VCM);
+    assert_eq!(print_and_cut[0].file_type, FileType::PrintAndCut);
+    assert_eq!(print_and_cut[0].marks, 4);
+}
+
+#[test]
+fn decodes_thumbnail_sizes() {
+    for (path, size) in [
+        (
+            "tests/samples/test/23 February New Print to Cut Test.fcm",
+            (88, 88),
+        ),
+        ("tests/samples/brother/project100_part1.fcm", (92, 100)),
+        ("tests/samples/test/longboi.fcm", (78, 128)),
+    ] {
+        let file = FcmFile::from_file(path).unwrap();
+        let thumbnail = file.file_header.decode_thumbnail().unwrap();
+        assert_eq!((thumbnail.width, thumbnail.height), size, "{}", path);
+        assert!(thumbnail.pixels.iter().any(|&black| black), "{}", path);
+        assert!(thumbnail.pixels.iter().any(|&black| !black), "{}", path);
+    }
+}